@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use crate::models::{ParsedSession, SessionFile};
+use crate::parser::SessionState;
+
+/// Bump whenever `ParsedSession` or the parsers change shape, so stale entries are dropped.
+const CACHE_VERSION: i64 = 23;
+
+/// What gets stored per session file. Claude files keep their resumable parse
+/// state so appended lines can be folded in; Cursor sessions store the result.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum CachedParse {
+    Claude(Box<SessionState>),
+    Cursor(Box<ParsedSession>),
+}
+
+/// Persistent store of parsed sessions, keyed by session file and validated by size + mtime.
+pub struct ParseCache {
+    conn: Connection,
+}
+
+/// A parsed session file held in memory, validated by the file's size and mtime
+pub struct MemoEntry {
+    size: u64,
+    mtime_ms: i64,
+    parse: CachedParse,
+}
+
+/// In-memory parse results for every known session file, keyed by `cache_key`.
+/// Seeded from the on-disk cache and reused across live reloads.
+#[derive(Default)]
+pub struct SessionMemo {
+    entries: HashMap<String, MemoEntry>,
+}
+
+pub fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("claude-tracker")
+        .join("parse-cache.sqlite")
+}
+
+/// Cache key for a session file. Cursor sessions share one DB, so the id is part of the key.
+pub fn cache_key(sf: &SessionFile) -> String {
+    format!("{}:{}", sf.path, sf.id)
+}
+
+fn mtime_ms(sf: &SessionFile) -> Option<i64> {
+    sf.modified
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+}
+
+impl ParseCache {
+    pub fn open() -> Result<Self> {
+        let path = cache_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Creating cache dir {}", dir.display()))?;
+        }
+
+        let conn = Connection::open(&path)
+            .with_context(|| format!("Opening parse cache {}", path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+             CREATE TABLE IF NOT EXISTS sessions (
+                 key TEXT PRIMARY KEY,
+                 size INTEGER NOT NULL,
+                 mtime_ms INTEGER NOT NULL,
+                 data TEXT NOT NULL
+             );",
+        )?;
+
+        // Parses depend on the code version and on the configured edit tools
+        let meta = |key: &str| -> Option<i64> {
+            conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+                .ok()
+        };
+        let edit_tools = crate::edits::fingerprint();
+        if meta("version") != Some(CACHE_VERSION) || meta("edit_tools") != Some(edit_tools) {
+            conn.execute("DELETE FROM sessions", [])?;
+            conn.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('version', ?1), ('edit_tools', ?2)",
+                [CACHE_VERSION, edit_tools],
+            )?;
+        }
+
+        Ok(Self { conn })
+    }
+
+    /// Read and deserialize every cached row
+    pub fn load_all(&self) -> SessionMemo {
+        let mut stmt = match self.conn.prepare("SELECT key, size, mtime_ms, data FROM sessions") {
+            Ok(s) => s,
+            Err(_) => return SessionMemo::default(),
+        };
+
+        let rows: Vec<(String, u64, i64, String)> = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get(2)?,
+                    row.get(3)?,
+                ))
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default();
+
+        let entries = rows
+            .into_par_iter()
+            .filter_map(|(key, size, mtime_ms, data)| {
+                let parse = serde_json::from_str(&data).ok()?;
+                Some((key, MemoEntry { size, mtime_ms, parse }))
+            })
+            .collect();
+
+        SessionMemo { entries }
+    }
+
+    /// Write the given memo entries and drop rows for files that no longer exist
+    pub fn store(&mut self, memo: &SessionMemo, changed: &HashSet<String>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO sessions (key, size, mtime_ms, data) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for key in changed {
+                let Some(e) = memo.entries.get(key) else { continue };
+                let Ok(data) = serde_json::to_string(&e.parse) else { continue };
+                insert.execute(params![key, e.size as i64, e.mtime_ms, data])?;
+            }
+
+            let stale: Vec<String> = {
+                let mut stmt = tx.prepare("SELECT key FROM sessions")?;
+                let keys = stmt
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .filter_map(|r| r.ok())
+                    .filter(|k| !memo.entries.contains_key(k))
+                    .collect();
+                keys
+            };
+            let mut delete = tx.prepare("DELETE FROM sessions WHERE key = ?1")?;
+            for key in &stale {
+                delete.execute([key])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove every cached session
+    pub fn clear(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM sessions", [])?)
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .unwrap_or(0)
+    }
+}
+
+impl SessionMemo {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Take the entry for a session file out of the memo so it can be refreshed
+    pub fn take(&mut self, sf: &SessionFile) -> Option<MemoEntry> {
+        self.entries.remove(&cache_key(sf))
+    }
+
+    /// Put a refreshed entry back; files without an mtime are not memoized
+    pub fn put(&mut self, sf: &SessionFile, parse: CachedParse) {
+        if let Some(mtime_ms) = mtime_ms(sf) {
+            self.entries.insert(
+                cache_key(sf),
+                MemoEntry { size: sf.size, mtime_ms, parse },
+            );
+        }
+    }
+
+    /// Drop entries for files that were not part of the latest scan
+    pub fn retain_keys(&mut self, seen_keys: &HashSet<String>) {
+        self.entries.retain(|k, _| seen_keys.contains(k));
+    }
+
+    /// Forget every Cursor session, e.g. after the global Cursor DB changed
+    pub fn invalidate_cursor(&mut self) {
+        self.entries.retain(|_, e| !matches!(e.parse, CachedParse::Cursor(_)));
+    }
+}
+
+impl MemoEntry {
+    pub fn is_unchanged(&self, sf: &SessionFile) -> bool {
+        self.size == sf.size && Some(self.mtime_ms) == mtime_ms(sf)
+    }
+
+    /// The parse state of a Claude file, if it can be resumed for the file's current size
+    pub fn into_state(self, sf: &SessionFile) -> Option<SessionState> {
+        match self.parse {
+            CachedParse::Claude(state) if sf.size >= self.size && state.offset() <= sf.size => {
+                Some(*state)
+            }
+            _ => None,
+        }
+    }
+
+    /// The cached Cursor session, if its database is unchanged
+    pub fn into_session(self, sf: &SessionFile) -> Option<ParsedSession> {
+        if !self.is_unchanged(sf) {
+            return None;
+        }
+        match self.parse {
+            CachedParse::Cursor(session) => Some(*session),
+            CachedParse::Claude(_) => None,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+
+use crate::display::{
+    print_cli_table, print_daily_table, print_json, print_json_value, print_projects_json,
+    print_calls_table, print_commands_json, print_projects_table, print_session_detail,
+    print_sessions_json, print_sessions_table, print_tools_json, print_tools_table, sorted_tools,
+};
+use crate::format::format_number;
+use crate::models::{DataSource, ParsedSession, ProjectSummary};
+use crate::cache::{cache_path, ParseCache};
+use crate::shell::group_by_program;
+use crate::tui_app::SortColumn;
+use crate::LoadOptions;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Launch the interactive TUI (default)
+    Tui,
+
+    /// Print the summary header and project table
+    Summary,
+
+    /// List projects with aggregated usage
+    Projects {
+        /// Only include projects with sessions from this source
+        #[arg(long, value_enum)]
+        source: Option<DataSource>,
+
+        /// Sort column
+        #[arg(long, value_enum, default_value = "last-active")]
+        sort: SortColumn,
+
+        /// Only include projects whose name or path contains this text
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Maximum number of projects to show
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+
+    /// List sessions of a project (matched by name, path or id)
+    Sessions {
+        project: String,
+
+        /// Only include sessions from this source
+        #[arg(long, value_enum)]
+        source: Option<DataSource>,
+
+        /// Maximum number of sessions to show (most recent first)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+
+    /// Show a single session (matched by id or unique id prefix)
+    Session {
+        id: String,
+
+        /// Also print the message thread
+        #[arg(long)]
+        messages: bool,
+    },
+
+    /// Activity per day
+    Daily {
+        /// Only show the most recent N days with activity
+        #[arg(long)]
+        days: Option<usize>,
+    },
+
+    /// Tool usage totals
+    Tools {
+        /// Restrict to a single project (matched by name, path or id)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Maximum number of tools to show
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+
+    /// Shell commands run through the Bash tool, by program and subcommand
+    Commands {
+        /// Restrict to a single project (matched by name, path or id)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Group by program only (`git` instead of `git commit`, `git push`, ...)
+        #[arg(long)]
+        programs: bool,
+
+        /// Maximum number of commands to show
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+
+    /// Inspect or clear the on-disk parse cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Show the cache location and number of cached sessions
+    Info,
+    /// Delete every cached session so the next run reparses everything
+    Clear,
+}
+
+/// Run a non-TUI subcommand
+pub fn run(command: Command, json: bool, opts: &LoadOptions) -> Result<()> {
+    if let Command::Cache { action } = command {
+        return run_cache(action);
+    }
+
+    let (mut projects, metrics) = crate::load_data(None, opts)?;
+
+    match command {
+        Command::Tui | Command::Cache { .. } => unreachable!("handled before loading"),
+        Command::Summary => {
+            if json {
+                print_json(&projects, &metrics);
+            } else {
+                print_cli_table(&projects, &metrics);
+            }
+        }
+        Command::Projects { source, sort, filter, limit } => {
+            if let Some(src) = source {
+                projects.retain(|p| p.sources.contains(&src));
+            }
+            if let Some(ref query) = filter {
+                let query = query.to_lowercase();
+                projects.retain(|p| {
+                    p.name.to_lowercase().contains(&query) || p.path.to_lowercase().contains(&query)
+                });
+            }
+            projects.sort_by(|a, b| sort.compare(a, b));
+            if let Some(n) = limit {
+                projects.truncate(n);
+            }
+
+            if json {
+                print_projects_json(&projects);
+            } else {
+                print_projects_table(&projects);
+            }
+        }
+        Command::Sessions { project, source, limit } => {
+            let project = find_project(&projects, &project)?;
+            let mut sessions: Vec<&ParsedSession> = project
+                .sessions
+                .iter()
+                .filter(|s| source.is_none_or(|src| s.source == src))
+                .collect();
+            sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+            if let Some(n) = limit {
+                sessions.truncate(n);
+            }
+
+            if json {
+                print_sessions_json(&sessions);
+            } else {
+                print_sessions_table(project, &sessions);
+            }
+        }
+        Command::Session { id, messages } => {
+            let (project, session) = find_session(&projects, &id)?;
+            if json {
+                print_json_value(session);
+            } else {
+                print_session_detail(project, session, messages);
+            }
+        }
+        Command::Daily { days } => {
+            let mut timeline = metrics.timeline;
+            if let Some(n) = days {
+                let skip = timeline.len().saturating_sub(n);
+                timeline.drain(..skip);
+            }
+
+            if json {
+                print_json_value(&timeline);
+            } else {
+                print_daily_table(&timeline);
+            }
+        }
+        Command::Tools { project, limit } => {
+            let (usage, outcomes) = match project {
+                Some(ref query) => {
+                    let p = find_project(&projects, query)?;
+                    (&p.tool_usage, &p.tool_outcomes)
+                }
+                None => (&metrics.tool_usage, &metrics.tool_outcomes),
+            };
+            let mut tools = sorted_tools(usage);
+            if let Some(n) = limit {
+                tools.truncate(n);
+            }
+
+            if json {
+                print_tools_json(&tools, outcomes);
+            } else {
+                print_tools_table(&tools, outcomes);
+            }
+        }
+        Command::Commands { project, programs, limit } => {
+            let (usage, outcomes) = match project {
+                Some(ref query) => {
+                    let p = find_project(&projects, query)?;
+                    (&p.command_usage, &p.command_outcomes)
+                }
+                None => (&metrics.command_usage, &metrics.command_outcomes),
+            };
+            let grouped;
+            let (usage, outcomes) = if programs {
+                grouped = group_by_program(usage, outcomes);
+                (&grouped.0, &grouped.1)
+            } else {
+                (usage, outcomes)
+            };
+            let mut commands = sorted_tools(usage);
+            if let Some(n) = limit {
+                commands.truncate(n);
+            }
+
+            if json {
+                print_commands_json(&commands, outcomes);
+            } else {
+                let label = if programs { "Program" } else { "Command" };
+                print_calls_table(label, &commands, outcomes);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_cache(action: CacheAction) -> Result<()> {
+    let cache = ParseCache::open()?;
+    match action {
+        CacheAction::Info => {
+            let path = cache_path();
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            println!("Path:     {}", path.display());
+            println!("Sessions: {}", cache.entry_count());
+            println!("Size:     {}B", format_number(size));
+        }
+        CacheAction::Clear => {
+            let removed = cache.clear()?;
+            println!("Removed {} cached sessions", removed);
+        }
+    }
+    Ok(())
+}
+
+/// Find a project by exact id/name/path, falling back to a unique substring match
+fn find_project<'a>(projects: &'a [ProjectSummary], query: &str) -> Result<&'a ProjectSummary> {
+    let q = query.to_lowercase();
+    let trimmed = query.trim_end_matches('/');
+
+    if let Some(p) = projects.iter().find(|p| {
+        p.id == query || p.path.trim_end_matches('/') == trimmed || p.name.to_lowercase() == q
+    }) {
+        return Ok(p);
+    }
+
+    let matches: Vec<&ProjectSummary> = projects
+        .iter()
+        .filter(|p| p.name.to_lowercase().contains(&q) || p.path.to_lowercase().contains(&q))
+        .collect();
+
+    match matches.len() {
+        0 => bail!("No project matches '{}'", query),
+        1 => Ok(matches[0]),
+        _ => {
+            let names: Vec<&str> = matches.iter().map(|p| p.path.as_str()).collect();
+            bail!("'{}' matches several projects:\n  {}", query, names.join("\n  "))
+        }
+    }
+}
+
+/// Find a session or subagent thread by id or unique id prefix across all projects
+fn find_session<'a>(
+    projects: &'a [ProjectSummary],
+    id: &str,
+) -> Result<(&'a ProjectSummary, &'a ParsedSession)> {
+    let mut matches: Vec<(&ProjectSummary, &ParsedSession)> = Vec::new();
+    for p in projects {
+        // Subagent threads can be looked up by their own id too
+        for s in p.sessions.iter().flat_map(|s| std::iter::once(s).chain(&s.agents)) {
+            if s.session_id == id {
+                return Ok((p, s));
+            }
+            if s.session_id.starts_with(id) {
+                matches.push((p, s));
+            }
+        }
+    }
+
+    match matches.len() {
+        0 => bail!("No session matches '{}'", id),
+        1 => Ok(matches[0]),
+        n => bail!("'{}' is ambiguous ({} sessions match)", id, n),
+    }
+}
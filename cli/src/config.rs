@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::edits::{EditToolMapping, LineCounting};
+
+/// Settings read from `config.toml` in the claude-tracker config dir
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Extra Claude data roots, scanned in addition to the default locations
+    pub claude_dirs: Vec<PathBuf>,
+    /// Cursor `User` dirs to scan instead of the auto-detected ones
+    pub cursor_dirs: Vec<PathBuf>,
+    /// Extra tools (e.g. MCP editing tools) whose inputs count toward lines changed
+    pub edit_tools: Vec<EditToolMapping>,
+    /// `diff` (default) counts only the lines an edit changed; `totals` counts every line
+    /// of the replaced and replacing text
+    pub line_counting: LineCounting,
+}
+
+/// `~/.config/claude-tracker` (or the platform equivalent)
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("claude-tracker")
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// Load the config file. A missing file yields the defaults; a malformed one is an error.
+pub fn load_config() -> Result<Config> {
+    let path = config_path();
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(e).with_context(|| format!("Reading {}", path.display())),
+    };
+
+    let mut config: Config =
+        toml::from_str(&raw).with_context(|| format!("Parsing {}", path.display()))?;
+    for dir in config.claude_dirs.iter_mut().chain(config.cursor_dirs.iter_mut()) {
+        *dir = expand_tilde(dir);
+    }
+    Ok(config)
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
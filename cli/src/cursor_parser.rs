@@ -1,11 +1,15 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rusqlite::Connection;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::Path;
 
+use crate::cursor_scanner::global_db_for_workspace_db;
 use crate::models::{
-    ConversationMessage, DataSource, FileContribution, ParsedSession, TokenTotals,
+    ConversationMessage, CostParts, DataSource, DedupStats, FileContribution, ParsedSession,
+    TokenTotals,
 };
+use crate::pricing::{totals_cost, CURSOR_MODEL, UNKNOWN_MODEL};
+use crate::window::TimeWindow;
 
 /// Cursor tool names → normalized names (matching Claude Code conventions).
 fn normalize_tool(name: &str) -> &str {
@@ -27,17 +31,6 @@ fn normalize_tool(name: &str) -> &str {
 const BUBBLE_USER: i64 = 1;
 const BUBBLE_ASSISTANT: i64 = 2;
 
-fn cursor_global_db_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("~"))
-        .join("Library")
-        .join("Application Support")
-        .join("Cursor")
-        .join("User")
-        .join("globalStorage")
-        .join("state.vscdb")
-}
-
 #[derive(Debug, serde::Deserialize)]
 struct CursorBubble {
     #[serde(rename = "type")]
@@ -98,13 +91,14 @@ struct ComposerData {
     all_composers: Option<Vec<ComposerHead>>,
 }
 
-/// Parse a Cursor session (composer) from SQLite databases.
+/// Parse a Cursor session (composer) from SQLite databases, keeping only bubbles inside `window`.
 pub fn parse_cursor_session(
     db_path: &str,
     session_id: &str,
     project_id: &str,
+    window: &TimeWindow,
 ) -> Result<ParsedSession> {
-    let bubbles = load_bubbles_from_global(session_id)?;
+    let bubbles = load_bubbles_from_global(db_path, session_id)?;
     let created_at = get_composer_created_at(db_path, session_id);
 
     Ok(build_parsed_session(
@@ -112,6 +106,7 @@ pub fn parse_cursor_session(
         session_id,
         project_id,
         &created_at,
+        window,
     ))
 }
 
@@ -159,9 +154,11 @@ fn get_composer_created_at(db_path: &str, composer_id: &str) -> String {
     String::new()
 }
 
-/// Load bubbles from the global Cursor state.vscdb.
-fn load_bubbles_from_global(composer_id: &str) -> Result<Vec<CursorBubble>> {
-    let db_path = cursor_global_db_path();
+/// Load bubbles from the global Cursor state.vscdb next to the given workspace DB.
+fn load_bubbles_from_global(workspace_db: &str, composer_id: &str) -> Result<Vec<CursorBubble>> {
+    let Some(db_path) = global_db_for_workspace_db(Path::new(workspace_db)) else {
+        return Ok(vec![]);
+    };
     if !db_path.exists() {
         return Ok(vec![]);
     }
@@ -201,6 +198,34 @@ fn load_bubbles_from_global(composer_id: &str) -> Result<Vec<CursorBubble>> {
     Ok(bubbles)
 }
 
+/// Read one bubble back from the global database with its text in full; parsing caps
+/// assistant text
+pub fn read_full_bubble(
+    workspace_db: &str,
+    composer_id: &str,
+    message: &ConversationMessage,
+) -> Result<ConversationMessage> {
+    let Some(db_path) = global_db_for_workspace_db(Path::new(workspace_db)) else {
+        bail!("no global Cursor database for {}", workspace_db);
+    };
+    let conn = Connection::open_with_flags(
+        &db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?;
+
+    let key = format!("bubbleId:{}:{}", composer_id, message.uuid);
+    let value: String = conn.query_row(
+        "SELECT value FROM cursorDiskKV WHERE key = ?1",
+        [&key],
+        |row| row.get(0),
+    )?;
+    let bubble: CursorBubble = serde_json::from_str(&value)?;
+
+    let mut full = message.clone();
+    full.content = bubble.text.unwrap_or_default();
+    Ok(full)
+}
+
 fn resolve_timestamp(raw: Option<f64>, base_epoch_ms: f64) -> Option<f64> {
     let val = raw?;
     if val <= 0.0 {
@@ -228,6 +253,7 @@ fn build_parsed_session(
     session_id: &str,
     project_id: &str,
     composer_created_at: &str,
+    window: &TimeWindow,
 ) -> ParsedSession {
     let mut messages: Vec<ConversationMessage> = Vec::new();
     let mut tool_usage: HashMap<String, u64> = HashMap::new();
@@ -266,9 +292,13 @@ fn build_parsed_session(
         let end_time = resolve_timestamp(raw_end, base_epoch_ms);
 
         let ts = start_time
-            .map(|t| ms_to_iso(t))
+            .map(ms_to_iso)
             .unwrap_or_else(|| composer_created_at.to_string());
 
+        if !window.contains_ts(&ts) {
+            continue;
+        }
+
         if !ts.is_empty() {
             if started_at.is_empty() {
                 started_at = ts.clone();
@@ -308,6 +338,16 @@ fn build_parsed_session(
                     .unwrap_or_else(|| format!("cursor-{}-{}", session_id, messages.len())),
                 usage: None,
                 content: text.to_string(),
+                cost: 0.0,
+                model: None,
+                message_id: None,
+                request_id: None,
+                duplicate: false,
+                thinking: String::new(),
+                thinking_chars: 0,
+                thinking_blocks: 0,
+                tool_uses: Vec::new(),
+                edits: Vec::new(),
             });
         } else if bubble_type == BUBBLE_ASSISTANT {
             // Count tokens
@@ -369,20 +409,34 @@ fn build_parsed_session(
                     .unwrap_or_else(|| format!("cursor-{}-{}", session_id, messages.len())),
                 usage: None,
                 content,
+                cost: 0.0,
+                model: None,
+                message_id: None,
+                request_id: None,
+                duplicate: false,
+                thinking: String::new(),
+                thinking_chars: 0,
+                thinking_blocks: 0,
+                tool_uses: Vec::new(),
+                edits: Vec::new(),
             });
         }
     }
 
-    ParsedSession {
+    let mut session = ParsedSession {
         session_id: session_id.to_string(),
         project_id: project_id.to_string(),
         cwd: String::new(),
         messages,
         tool_usage,
+        tool_outcomes: HashMap::new(),
+        command_usage: HashMap::new(),
+        command_outcomes: HashMap::new(),
         total_tokens: tokens,
         duration_ms,
         lines_added,
         lines_removed,
+        partial_edits: 0,
         file_contributions,
         first_prompt,
         started_at: if started_at.is_empty() {
@@ -400,5 +454,46 @@ fn build_parsed_session(
         human_chars,
         model: String::new(),
         source: DataSource::Cursor,
+        cost: 0.0,
+        cost_parts: CostParts::default(),
+        cost_by_model: HashMap::new(),
+        unpriced_models: Vec::new(),
+        parent_session: None,
+        agents: Vec::new(),
+        deduped: DedupStats::default(),
+        thinking_chars: 0,
+        thinking_turns: 0,
+        summary: String::new(),
+        continues_from: Vec::new(),
+        previous_session: None,
+        thread_id: session_id.to_string(),
+        commits: Vec::new(),
+        source_path: String::new(),
+    };
+    price_session(&mut session);
+    session
+}
+
+/// (Re)compute a session's cost from its token totals. Cursor bubbles don't name
+/// a model and only report running totals, so the session is priced as a whole.
+pub fn price_session(session: &mut ParsedSession) {
+    session.cost_by_model.clear();
+    session.unpriced_models.clear();
+
+    match totals_cost(CURSOR_MODEL, &session.total_tokens, &session.started_at) {
+        Some(parts) => {
+            session.cost_parts = parts;
+            session.cost = parts.total();
+            if session.cost > 0.0 {
+                session.cost_by_model.insert(UNKNOWN_MODEL.to_string(), session.cost);
+            }
+        }
+        None => {
+            session.cost_parts = CostParts::default();
+            session.cost = 0.0;
+            if session.total_tokens.total() > 0 {
+                session.unpriced_models.push(UNKNOWN_MODEL.to_string());
+            }
+        }
     }
 }
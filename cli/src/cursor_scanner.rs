@@ -2,29 +2,64 @@ use anyhow::Result;
 use rusqlite::Connection;
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use crate::models::{DataSource, ScannedProject, SessionFile};
 
-fn cursor_workspace_storage_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("~"))
-        .join("Library")
-        .join("Application Support")
-        .join("Cursor")
-        .join("User")
-        .join("workspaceStorage")
+/// Environment variable that overrides the Cursor data location, like `--cursor-dir`
+pub const CURSOR_DIR_ENV: &str = "CT_CURSOR_DIR";
+
+/// Resolve the Cursor `User` directories to scan. Explicit overrides win;
+/// otherwise every platform location that exists is used.
+pub fn cursor_user_dirs(overrides: &[PathBuf]) -> Vec<PathBuf> {
+    if !overrides.is_empty() {
+        // Accept either the `User` dir itself or the Cursor app dir above it
+        return overrides
+            .iter()
+            .map(|d| {
+                let user = d.join("User");
+                if user.is_dir() {
+                    user
+                } else {
+                    d.clone()
+                }
+            })
+            .collect();
+    }
+
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("~"));
+    let candidates = [
+        // %APPDATA% on Windows, ~/Library/Application Support on macOS, $XDG_CONFIG_HOME on Linux
+        dirs::config_dir(),
+        Some(home.join(".config")),
+        Some(home.join("Library").join("Application Support")),
+        std::env::var_os("APPDATA").map(PathBuf::from),
+    ];
+
+    // Several candidates can point at the same place, so dedupe by resolved path
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .flatten()
+        .map(|base| base.join("Cursor").join("User"))
+        .filter(|dir| dir.is_dir())
+        .filter(|dir| seen.insert(dir.canonicalize().unwrap_or_else(|_| dir.clone())))
+        .collect()
+}
+
+pub fn cursor_workspace_storage_dir(user_dir: &Path) -> PathBuf {
+    user_dir.join("workspaceStorage")
+}
+
+pub fn cursor_global_db_path(user_dir: &Path) -> PathBuf {
+    user_dir.join("globalStorage").join("state.vscdb")
 }
 
-fn cursor_global_db_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("~"))
-        .join("Library")
-        .join("Application Support")
-        .join("Cursor")
-        .join("User")
-        .join("globalStorage")
-        .join("state.vscdb")
+/// The global DB belonging to a workspace DB (`<User>/workspaceStorage/<hash>/state.vscdb`)
+pub fn global_db_for_workspace_db(db_path: &Path) -> Option<PathBuf> {
+    let user_dir = db_path.parent()?.parent()?.parent()?;
+    Some(cursor_global_db_path(user_dir))
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -50,8 +85,8 @@ struct WorkspaceJson {
 }
 
 /// Get composer IDs that have actual bubble messages in the global DB.
-fn get_composer_ids_with_bubbles() -> HashSet<String> {
-    let db_path = cursor_global_db_path();
+fn get_composer_ids_with_bubbles(user_dir: &Path) -> HashSet<String> {
+    let db_path = cursor_global_db_path(user_dir);
     if !db_path.exists() {
         return HashSet::new();
     }
@@ -82,19 +117,29 @@ fn get_composer_ids_with_bubbles() -> HashSet<String> {
     ids.into_iter().collect()
 }
 
-/// Scan all Cursor workspace directories and return projects with session files.
-pub fn scan_cursor_projects() -> Result<Vec<ScannedProject>> {
-    let storage_dir = cursor_workspace_storage_dir();
+/// Scan the workspace directories of every Cursor `User` dir and return projects with session files.
+pub fn scan_cursor_projects(user_dirs: &[PathBuf]) -> Result<Vec<ScannedProject>> {
+    let mut projects = Vec::new();
+    for user_dir in user_dirs {
+        scan_user_dir(user_dir, &mut projects);
+    }
+    Ok(projects)
+}
+
+fn scan_user_dir(user_dir: &Path, projects: &mut Vec<ScannedProject>) {
+    let storage_dir = cursor_workspace_storage_dir(user_dir);
     if !storage_dir.exists() {
-        return Ok(vec![]);
+        return;
     }
 
-    let active_composers = get_composer_ids_with_bubbles();
-    let mut projects = Vec::new();
+    let active_composers = get_composer_ids_with_bubbles(user_dir);
+    // Bubbles live in the global DB, so it takes part in every session's fingerprint
+    let global_db = cursor_global_db_path(user_dir);
+    let global_files = [global_db.with_extension("vscdb-wal"), global_db];
 
     let entries = match fs::read_dir(&storage_dir) {
         Ok(e) => e,
-        Err(_) => return Ok(vec![]),
+        Err(_) => return,
     };
 
     for entry in entries {
@@ -164,14 +209,17 @@ pub fn scan_cursor_projects() -> Result<Vec<ScannedProject>> {
         }
 
         let project_id = format!("cursor-{}", dir_name);
+        let (size, modified) = fingerprint(global_files.iter().chain([&db_path]));
 
         let session_files: Vec<SessionFile> = active
             .iter()
             .map(|c| SessionFile {
                 id: c.composer_id.clone(),
                 path: db_path.to_string_lossy().to_string(),
-                size: 0, // not meaningful for SQLite-backed sessions
+                size,
+                modified,
                 source: DataSource::Cursor,
+                parent_session: None,
             })
             .collect();
 
@@ -183,8 +231,18 @@ pub fn scan_cursor_projects() -> Result<Vec<ScannedProject>> {
             session_files,
         });
     }
+}
 
-    Ok(projects)
+/// Combined size and latest mtime of the files a Cursor session is read from.
+/// Writes land in the WAL before the DB itself, so it counts as well.
+fn fingerprint<'a>(paths: impl Iterator<Item = &'a PathBuf>) -> (u64, Option<SystemTime>) {
+    let mut size = 0;
+    let mut modified = None;
+    for meta in paths.filter_map(|p| fs::metadata(p).ok()) {
+        size += meta.len();
+        modified = modified.max(meta.modified().ok());
+    }
+    (size, modified)
 }
 
 fn read_composers(db_path: &PathBuf) -> Option<Vec<ComposerHead>> {
@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{
+    ConversationMessage, CostParts, DedupStats, FileContribution, ParsedSession, ToolOutcomes,
+};
+use crate::pricing::{usage_cost, UNKNOWN_MODEL};
+
+/// Usage taken out of a session, to be taken out of its parent too
+#[derive(Default)]
+struct Removed {
+    stats: DedupStats,
+    cost_parts: CostParts,
+    cost_by_model: HashMap<String, f64>,
+    activity: Activity,
+}
+
+/// Tool calls, edits and thinking of repeated messages
+#[derive(Default)]
+struct Activity {
+    tool_usage: HashMap<String, u64>,
+    tool_outcomes: HashMap<String, ToolOutcomes>,
+    command_usage: HashMap<String, u64>,
+    command_outcomes: HashMap<String, ToolOutcomes>,
+    lines_added: u64,
+    lines_removed: u64,
+    partial_edits: u64,
+    file_contributions: HashMap<String, FileContribution>,
+    thinking_chars: u64,
+    thinking_turns: u64,
+}
+
+impl Activity {
+    /// Count a message the way `SessionState::to_session` counted it
+    fn record(&mut self, msg: &ConversationMessage) {
+        for tool in &msg.tool_uses {
+            *self.tool_usage.entry(tool.name.clone()).or_insert(0) += 1;
+            if let Some(ref command) = tool.command {
+                *self.command_usage.entry(command.clone()).or_insert(0) += 1;
+            }
+            if let Some(ref result) = tool.result {
+                self.tool_outcomes.entry(tool.name.clone()).or_default().record(result.is_error);
+                if let Some(ref command) = tool.command {
+                    self.command_outcomes.entry(command.clone()).or_default().record(result.is_error);
+                }
+            }
+        }
+        for edit in &msg.edits {
+            self.lines_added += edit.added;
+            self.lines_removed += edit.removed;
+            self.partial_edits += u64::from(edit.partial);
+            if let Some(ref fp) = edit.file_path {
+                let fc = self
+                    .file_contributions
+                    .entry(fp.clone())
+                    .or_insert(FileContribution { added: 0, removed: 0 });
+                fc.added += edit.added;
+                fc.removed += edit.removed;
+            }
+        }
+        if msg.thinking_blocks > 0 {
+            self.thinking_turns += 1;
+            self.thinking_chars += msg.thinking_chars;
+        }
+    }
+
+    fn add(&mut self, other: Activity) {
+        for (tool, count) in other.tool_usage {
+            *self.tool_usage.entry(tool).or_insert(0) += count;
+        }
+        for (tool, outcome) in other.tool_outcomes {
+            self.tool_outcomes.entry(tool).or_default().add(&outcome);
+        }
+        for (command, count) in other.command_usage {
+            *self.command_usage.entry(command).or_insert(0) += count;
+        }
+        for (command, outcome) in other.command_outcomes {
+            self.command_outcomes.entry(command).or_default().add(&outcome);
+        }
+        self.lines_added += other.lines_added;
+        self.lines_removed += other.lines_removed;
+        self.partial_edits += other.partial_edits;
+        for (path, fc) in other.file_contributions {
+            let own = self
+                .file_contributions
+                .entry(path)
+                .or_insert(FileContribution { added: 0, removed: 0 });
+            own.added += fc.added;
+            own.removed += fc.removed;
+        }
+        self.thinking_chars += other.thinking_chars;
+        self.thinking_turns += other.thinking_turns;
+    }
+
+    /// Take this activity out of a session's totals, dropping entries that reach zero
+    fn take_from(&self, session: &mut ParsedSession) {
+        for (tool, count) in &self.tool_usage {
+            if let Some(c) = session.tool_usage.get_mut(tool) {
+                *c = c.saturating_sub(*count);
+            }
+        }
+        session.tool_usage.retain(|_, c| *c > 0);
+        for (command, count) in &self.command_usage {
+            if let Some(c) = session.command_usage.get_mut(command) {
+                *c = c.saturating_sub(*count);
+            }
+        }
+        session.command_usage.retain(|_, c| *c > 0);
+        for (tool, outcome) in &self.tool_outcomes {
+            if let Some(o) = session.tool_outcomes.get_mut(tool) {
+                o.subtract(outcome);
+            }
+        }
+        session.tool_outcomes.retain(|_, o| o.succeeded + o.failed > 0);
+        for (command, outcome) in &self.command_outcomes {
+            if let Some(o) = session.command_outcomes.get_mut(command) {
+                o.subtract(outcome);
+            }
+        }
+        session.command_outcomes.retain(|_, o| o.succeeded + o.failed > 0);
+
+        session.lines_added = session.lines_added.saturating_sub(self.lines_added);
+        session.lines_removed = session.lines_removed.saturating_sub(self.lines_removed);
+        session.partial_edits = session.partial_edits.saturating_sub(self.partial_edits);
+        for (path, removed) in &self.file_contributions {
+            let Some(fc) = session.file_contributions.get_mut(path) else {
+                continue;
+            };
+            fc.added = fc.added.saturating_sub(removed.added);
+            fc.removed = fc.removed.saturating_sub(removed.removed);
+            // Only edited by repeated messages: the file belongs to the earlier session
+            if fc.added == 0 && fc.removed == 0 {
+                session.file_contributions.remove(path);
+            }
+        }
+
+        session.thinking_chars = session.thinking_chars.saturating_sub(self.thinking_chars);
+        session.thinking_turns = session.thinking_turns.saturating_sub(self.thinking_turns);
+    }
+}
+
+/// Resumed and forked sessions copy earlier messages into their new file. Keep each
+/// assistant message's usage, tool calls, edits and thinking (keyed by message id and
+/// request id) in the earliest session that has it and take them out of every later copy.
+pub fn dedup_sessions(sessions: &mut [ParsedSession]) {
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    let mut seen: HashSet<String> = HashSet::new();
+    for session in sessions.iter_mut() {
+        remove_repeats(session, &mut seen);
+    }
+}
+
+fn remove_repeats(session: &mut ParsedSession, seen: &mut HashSet<String>) -> Removed {
+    let mut removed = Removed::default();
+
+    for msg in &mut session.messages {
+        let Some(key) = msg.dedup_key() else {
+            continue;
+        };
+        if seen.insert(key) {
+            continue;
+        }
+
+        msg.duplicate = true;
+        removed.stats.messages += 1;
+        removed.activity.record(msg);
+        if let Some(ref usage) = msg.usage {
+            removed.stats.tokens.add_usage(usage);
+            let model = msg.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+            if let Some(parts) = usage_cost(model, usage, &msg.timestamp) {
+                removed.cost_parts.add(&parts);
+                *removed.cost_by_model.entry(model.to_string()).or_insert(0.0) += parts.total();
+            }
+        }
+        msg.cost = 0.0;
+    }
+
+    // Inline subagent threads are already part of this session's totals
+    for agent in &mut session.agents {
+        let from_agent = remove_repeats(agent, seen);
+        removed.stats.add(&from_agent.stats);
+        removed.cost_parts.add(&from_agent.cost_parts);
+        for (model, cost) in from_agent.cost_by_model {
+            *removed.cost_by_model.entry(model).or_insert(0.0) += cost;
+        }
+        removed.activity.add(from_agent.activity);
+    }
+
+    if !removed.stats.is_empty() {
+        removed.stats.cost = removed.cost_parts.total();
+        session.total_tokens.subtract(&removed.stats.tokens);
+        session.cost_parts.subtract(&removed.cost_parts);
+        session.cost = session.cost_parts.total();
+        for (model, cost) in &removed.cost_by_model {
+            if let Some(c) = session.cost_by_model.get_mut(model) {
+                *c -= cost;
+            }
+        }
+        session.cost_by_model.retain(|_, c| *c > 1e-9);
+        removed.activity.take_from(session);
+        session.deduped.add(&removed.stats);
+    }
+    removed
+}
@@ -1,11 +1,24 @@
 use colored::Colorize;
 use tabled::{builder::Builder, settings::Style};
 
-use crate::format::{format_cost, format_number, format_relative, short_model};
-use crate::models::{DataSource, GlobalMetrics, ProjectSummary};
+use std::collections::HashMap;
+
+use crate::format::{
+    format_commits, format_cost, format_cost_breakdown, format_duration, format_percent, format_number,
+    format_relative, format_thinking, short_model, truncate,
+};
+use crate::models::{
+    DataSource, DedupStats, GlobalMetrics, ParsedSession, ProjectSummary, TimelineEntry,
+    ToolOutcomes,
+};
+use crate::shell::program_of;
 
 pub fn print_cli_table(projects: &[ProjectSummary], metrics: &GlobalMetrics) {
-    // Header stats
+    print_summary_header(metrics);
+    print_projects_table(projects);
+}
+
+fn print_summary_header(metrics: &GlobalMetrics) {
     println!();
     println!(
         "{}  {} projects  {} sessions  {} messages  {} tokens  {} cost",
@@ -21,9 +34,39 @@ pub fn print_cli_table(projects: &[ProjectSummary], metrics: &GlobalMetrics) {
         format_number(metrics.total_lines_added).green(),
         format_number(metrics.total_lines_removed).red(),
     );
+    println!(
+        "  Cost: {} in / {} out / {} cache read / {} cache write",
+        format_cost(metrics.cost_parts.input),
+        format_cost(metrics.cost_parts.output),
+        format_cost(metrics.cost_parts.cache_read),
+        format_cost(metrics.cost_parts.cache_write),
+    );
+    if !metrics.cost_by_model.is_empty() {
+        println!("  Cost by model: {}", format_cost_breakdown(&metrics.cost_by_model));
+    }
+    if metrics.thinking_turns > 0 {
+        println!(
+            "  Thinking: {}",
+            format_thinking(metrics.thinking_turns, metrics.thinking_chars)
+        );
+    }
+    if !metrics.deduped.is_empty() {
+        println!("  {}", format_deduped(&metrics.deduped).dimmed());
+    }
     println!();
+}
+
+/// One-line note on the repeated usage left out of the totals
+fn format_deduped(deduped: &DedupStats) -> String {
+    format!(
+        "Not counted: {} messages repeated from earlier sessions ({} tokens, {})",
+        format_number(deduped.messages),
+        format_number(deduped.tokens.total()),
+        format_cost(deduped.cost),
+    )
+}
 
-    // Project table
+pub fn print_projects_table(projects: &[ProjectSummary]) {
     let mut builder = Builder::default();
     builder.push_record([
         "Project",
@@ -32,6 +75,7 @@ pub fn print_cli_table(projects: &[ProjectSummary], metrics: &GlobalMetrics) {
         "Messages",
         "Tokens",
         "Lines +/-",
+        "Thinking",
         "Cost",
         "Model",
         "Last Active",
@@ -46,6 +90,7 @@ pub fn print_cli_table(projects: &[ProjectSummary], metrics: &GlobalMetrics) {
             &p.message_count.to_string(),
             &format_number(p.total_tokens.total()),
             &format!("{}/{}", format_number(p.lines_added), format_number(p.lines_removed)),
+            &format_thinking(p.thinking_turns, p.thinking_chars),
             &format_cost(p.cost),
             &short_model(&p.model),
             &format_relative(&p.last_active),
@@ -67,6 +112,394 @@ fn source_label_str(sources: &[DataSource]) -> String {
     }
 }
 
+pub fn print_sessions_table(project: &ProjectSummary, sessions: &[&ParsedSession]) {
+    println!();
+    println!(
+        "{}  {}  {} sessions",
+        project.name.bold().cyan(),
+        project.path.dimmed(),
+        sessions.len().to_string().bold(),
+    );
+    if project.commit_count > 0 {
+        println!(
+            "  Commits: {}",
+            format_commits(project.commit_count, project.cost_per_commit())
+        );
+    }
+    println!();
+
+    let mut builder = Builder::default();
+    builder.push_record([
+        "Session",
+        "Thread",
+        "Source",
+        "First Prompt",
+        "Messages",
+        "Tokens",
+        "Duration",
+        "Lines +/-",
+        "Commits",
+        "Cost",
+        "Model",
+        "Started",
+    ]);
+
+    for s in sessions {
+        let thread = project
+            .thread_of(s)
+            .map(|(pos, t)| format!("{}/{}", pos + 1, t.session_ids.len()))
+            .unwrap_or_default();
+        builder.push_record([
+            &short_id(&s.session_id),
+            &thread,
+            s.source.label(),
+            &truncate(&s.first_prompt, 40),
+            &s.messages.len().to_string(),
+            &format_number(s.total_tokens.total()),
+            &format_duration(s.duration_ms),
+            &format!("{}/{}", format_number(s.lines_added), format_number(s.lines_removed)),
+            &s.commits.len().to_string(),
+            &format_cost(s.cost),
+            &short_model(&s.model),
+            &format_relative(&s.started_at),
+        ]);
+    }
+
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    println!();
+}
+
+pub fn print_session_detail(project: &ProjectSummary, session: &ParsedSession, show_messages: bool) {
+    println!();
+    println!(
+        "{}  {}  {}",
+        session.session_id.bold().cyan(),
+        project.name.bold(),
+        session.source.label().dimmed(),
+    );
+    println!("  Prompt:   {}", truncate(&session.first_prompt, 100));
+    println!(
+        "  Model:    {}",
+        if session.model.is_empty() { "-".to_string() } else { session.model.clone() }
+    );
+    println!(
+        "  Started:  {}  ({})",
+        session.started_at,
+        format_relative(&session.started_at)
+    );
+    println!("  Duration: {}", format_duration(session.duration_ms));
+    println!(
+        "  Tokens:   {} in / {} out / {} cache read / {} cache write",
+        format_number(session.total_tokens.input),
+        format_number(session.total_tokens.output),
+        format_number(session.total_tokens.cache_read),
+        format_number(session.total_tokens.cache_creation),
+    );
+    println!(
+        "  Cost:     {}  ({} in / {} out / {} cache read / {} cache write)",
+        format_cost(session.cost).green(),
+        format_cost(session.cost_parts.input),
+        format_cost(session.cost_parts.output),
+        format_cost(session.cost_parts.cache_read),
+        format_cost(session.cost_parts.cache_write),
+    );
+    if session.cost_by_model.len() > 1 {
+        println!("            {}", format_cost_breakdown(&session.cost_by_model).dimmed());
+    }
+    if !session.agents.is_empty() {
+        println!(
+            "            {}",
+            format!(
+                "incl. {} in {} subagent thread(s)",
+                format_cost(session.agents_cost()),
+                session.agents.len()
+            )
+            .dimmed()
+        );
+    }
+    if !session.deduped.is_empty() {
+        println!("            {}", format_deduped(&session.deduped).dimmed());
+    }
+    if let Some(ref parent) = session.parent_session {
+        println!("  Parent:   {}", parent);
+    }
+    if let Some((pos, thread)) = project.thread_of(session) {
+        let continues = match session.previous_session {
+            Some(ref prev) => format!(", continues {}", short_id(prev)),
+            None => String::new(),
+        };
+        println!(
+            "  Thread:   session {} of {}{}",
+            pos + 1,
+            thread.session_ids.len(),
+            continues
+        );
+        if !thread.summary.is_empty() {
+            println!("            {}", truncate(&thread.summary, 100).dimmed());
+        }
+        println!(
+            "            {}",
+            format!(
+                "combined: {} msgs · {} tokens · {} · {} · +{}/-{} lines",
+                thread.message_count,
+                format_number(thread.total_tokens.total()),
+                format_cost(thread.cost),
+                format_duration(thread.duration_ms),
+                format_number(thread.lines_added),
+                format_number(thread.lines_removed),
+            )
+            .dimmed()
+        );
+    }
+    let partial = match session.partial_edits {
+        0 => String::new(),
+        n => format!("  (at least; {} edits not fully countable)", n),
+    };
+    println!(
+        "  Lines:    {} added / {} removed{}",
+        format_number(session.lines_added).green(),
+        format_number(session.lines_removed).red(),
+        partial.dimmed(),
+    );
+    println!(
+        "  Thinking: {}",
+        format_thinking(session.thinking_turns, session.thinking_chars)
+    );
+    if !session.commits.is_empty() {
+        println!(
+            "  Commits:  {}",
+            format_commits(session.commits.len(), session.cost_per_commit())
+        );
+        for commit in &session.commits {
+            println!(
+                "            {}",
+                format!("{} {}", short_id(&commit.hash), truncate(&commit.subject, 90)).dimmed()
+            );
+        }
+    }
+    if !session.command_usage.is_empty() {
+        println!(
+            "  Commands: {}",
+            format_top_commands(&session.command_usage, &session.command_outcomes, 5)
+        );
+    }
+    println!();
+
+    if !session.file_contributions.is_empty() {
+        let mut files: Vec<_> = session.file_contributions.iter().collect();
+        files.sort_by_key(|(_, fc)| std::cmp::Reverse(fc.added + fc.removed));
+
+        let mut builder = Builder::default();
+        builder.push_record(["File", "+", "-"]);
+        for (path, fc) in files {
+            builder.push_record([path.as_str(), &fc.added.to_string(), &fc.removed.to_string()]);
+        }
+        println!("{}", builder.build().with(Style::rounded()));
+        println!();
+    }
+
+    if !session.agents.is_empty() {
+        let mut builder = Builder::default();
+        builder.push_record(["Agent", "Task", "Messages", "Tokens", "Cost", "Model"]);
+        for a in &session.agents {
+            builder.push_record([
+                &a.session_id,
+                &truncate(&a.first_prompt, 50),
+                &a.messages.len().to_string(),
+                &format_number(a.total_tokens.total()),
+                &format_cost(a.cost),
+                &short_model(&a.model),
+            ]);
+        }
+        println!("{}", builder.build().with(Style::rounded()));
+        println!();
+    }
+
+    if show_messages {
+        for msg in &session.messages {
+            let role = if msg.role == "user" {
+                "You".bold().cyan()
+            } else {
+                "Assistant".bold().magenta()
+            };
+            println!("── {} {}", role, format_relative(&msg.timestamp).dimmed());
+            if msg.thinking_blocks > 0 {
+                println!(
+                    "  {}",
+                    format!("▸ thinking ({} chars)", format_number(msg.thinking_chars)).dimmed()
+                );
+            }
+            for line in msg.content.lines() {
+                println!("  {}", line);
+            }
+            println!();
+        }
+    }
+}
+
+pub fn print_daily_table(timeline: &[TimelineEntry]) {
+    let mut builder = Builder::default();
+    builder.push_record(["Date", "Sessions", "Claude", "Cursor", "Messages", "Input", "Output"]);
+
+    for t in timeline {
+        builder.push_record([
+            &t.date,
+            &t.sessions.to_string(),
+            &t.claude_sessions.to_string(),
+            &t.cursor_sessions.to_string(),
+            &format_number(t.messages),
+            &format_number(t.token_input),
+            &format_number(t.token_output),
+        ]);
+    }
+
+    println!();
+    println!("{}", builder.build().with(Style::rounded()));
+    println!();
+}
+
+pub fn print_tools_table(tools: &[(String, u64)], outcomes: &HashMap<String, ToolOutcomes>) {
+    print_calls_table("Tool", tools, outcomes);
+}
+
+/// Calls per tool or command with their share and how many of them failed
+pub fn print_calls_table(label: &str, calls: &[(String, u64)], outcomes: &HashMap<String, ToolOutcomes>) {
+    let total: u64 = calls.iter().map(|(_, c)| c).sum();
+
+    let mut builder = Builder::default();
+    builder.push_record([label, "Calls", "Share", "Succeeded", "Failed", "Failure Rate"]);
+    for (name, count) in calls {
+        let share = if total > 0 { *count as f64 / total as f64 * 100.0 } else { 0.0 };
+        let outcome = outcomes.get(name).copied().unwrap_or_default();
+        builder.push_record([
+            name.as_str(),
+            &format_number(*count),
+            &format!("{:.1}%", share),
+            &format_number(outcome.succeeded),
+            &format_number(outcome.failed),
+            &outcome.failure_rate().map(format_percent).unwrap_or_default(),
+        ]);
+    }
+
+    println!();
+    println!("{}", builder.build().with(Style::rounded()));
+    println!();
+}
+
+/// Most-run commands with their counts, e.g. `cargo test ×4 (25% failed) · git status ×2`
+fn format_top_commands(
+    usage: &HashMap<String, u64>,
+    outcomes: &HashMap<String, ToolOutcomes>,
+    n: usize,
+) -> String {
+    let commands = sorted_tools(usage);
+    let mut parts: Vec<String> = commands
+        .iter()
+        .take(n)
+        .map(|(command, count)| {
+            let failed = outcomes
+                .get(command)
+                .and_then(|o| o.failure_rate())
+                .filter(|r| *r > 0.0)
+                .map(|r| format!(" ({} failed)", format_percent(r)))
+                .unwrap_or_default();
+            format!("{} ×{}{}", command, count, failed)
+        })
+        .collect();
+    if commands.len() > n {
+        parts.push(format!("+{} more", commands.len() - n));
+    }
+    parts.join(" · ")
+}
+
+/// Sort a tool usage map by call count, descending
+pub fn sorted_tools(tool_usage: &HashMap<String, u64>) -> Vec<(String, u64)> {
+    let mut tools: Vec<(String, u64)> = tool_usage
+        .iter()
+        .map(|(name, count)| (name.clone(), *count))
+        .collect();
+    tools.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    tools
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(8).collect()
+}
+
+#[derive(serde::Serialize)]
+struct ProjectJson<'a> {
+    name: &'a str,
+    path: &'a str,
+    source: String,
+    session_count: usize,
+    message_count: usize,
+    tokens_total: u64,
+    lines_added: u64,
+    lines_removed: u64,
+    cost: f64,
+    cost_by_model: &'a HashMap<String, f64>,
+    model: &'a str,
+    last_active: &'a str,
+    thread_count: usize,
+    thinking_chars: u64,
+    thinking_turns: u64,
+    commit_count: usize,
+    cost_per_commit: Option<f64>,
+}
+
+impl<'a> ProjectJson<'a> {
+    fn from_summary(p: &'a ProjectSummary) -> Self {
+        ProjectJson {
+            name: &p.name,
+            path: &p.path,
+            source: source_label_str(&p.sources),
+            session_count: p.session_count,
+            message_count: p.message_count,
+            tokens_total: p.total_tokens.total(),
+            lines_added: p.lines_added,
+            lines_removed: p.lines_removed,
+            cost: p.cost,
+            cost_by_model: &p.cost_by_model,
+            model: &p.model,
+            last_active: &p.last_active,
+            thread_count: p.threads.len(),
+            thinking_chars: p.thinking_chars,
+            thinking_turns: p.thinking_turns,
+            commit_count: p.commit_count,
+            cost_per_commit: p.cost_per_commit(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SessionJson<'a> {
+    session_id: &'a str,
+    source: DataSource,
+    first_prompt: &'a str,
+    started_at: &'a str,
+    last_active: &'a str,
+    message_count: usize,
+    tokens_input: u64,
+    tokens_output: u64,
+    tokens_cache_read: u64,
+    tokens_cache_creation: u64,
+    duration_ms: f64,
+    lines_added: u64,
+    lines_removed: u64,
+    cost: f64,
+    cost_by_model: &'a HashMap<String, f64>,
+    model: &'a str,
+    agent_count: usize,
+    agents_cost: f64,
+    thread_id: &'a str,
+    previous_session: Option<&'a str>,
+    thinking_chars: u64,
+    thinking_turns: u64,
+    commit_count: usize,
+    cost_per_commit: Option<f64>,
+}
+
 pub fn print_json(projects: &[ProjectSummary], metrics: &GlobalMetrics) {
     #[derive(serde::Serialize)]
     struct Output<'a> {
@@ -74,40 +507,97 @@ pub fn print_json(projects: &[ProjectSummary], metrics: &GlobalMetrics) {
         projects: Vec<ProjectJson<'a>>,
     }
 
-    #[derive(serde::Serialize)]
-    struct ProjectJson<'a> {
-        name: &'a str,
-        path: &'a str,
-        source: String,
-        session_count: usize,
-        message_count: usize,
-        tokens_total: u64,
-        lines_added: u64,
-        lines_removed: u64,
-        cost: f64,
-        model: &'a str,
-        last_active: &'a str,
-    }
-
     let output = Output {
         metrics,
-        projects: projects
-            .iter()
-            .map(|p| ProjectJson {
-                name: &p.name,
-                path: &p.path,
-                source: source_label_str(&p.sources),
-                session_count: p.session_count,
-                message_count: p.message_count,
-                tokens_total: p.total_tokens.total(),
-                lines_added: p.lines_added,
-                lines_removed: p.lines_removed,
-                cost: p.cost,
-                model: &p.model,
-                last_active: &p.last_active,
-            })
-            .collect(),
+        projects: projects.iter().map(ProjectJson::from_summary).collect(),
     };
 
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    print_json_value(&output);
+}
+
+pub fn print_projects_json(projects: &[ProjectSummary]) {
+    let rows: Vec<ProjectJson> = projects.iter().map(ProjectJson::from_summary).collect();
+    print_json_value(&rows);
+}
+
+pub fn print_sessions_json(sessions: &[&ParsedSession]) {
+    let rows: Vec<SessionJson> = sessions
+        .iter()
+        .map(|s| SessionJson {
+            session_id: &s.session_id,
+            source: s.source,
+            first_prompt: &s.first_prompt,
+            started_at: &s.started_at,
+            last_active: &s.last_active,
+            message_count: s.messages.len(),
+            tokens_input: s.total_tokens.input,
+            tokens_output: s.total_tokens.output,
+            tokens_cache_read: s.total_tokens.cache_read,
+            tokens_cache_creation: s.total_tokens.cache_creation,
+            duration_ms: s.duration_ms,
+            lines_added: s.lines_added,
+            lines_removed: s.lines_removed,
+            cost: s.cost,
+            cost_by_model: &s.cost_by_model,
+            model: &s.model,
+            agent_count: s.agents.len(),
+            agents_cost: s.agents_cost(),
+            thread_id: &s.thread_id,
+            previous_session: s.previous_session.as_deref(),
+            thinking_chars: s.thinking_chars,
+            thinking_turns: s.thinking_turns,
+            commit_count: s.commits.len(),
+            cost_per_commit: s.cost_per_commit(),
+        })
+        .collect();
+    print_json_value(&rows);
+}
+
+pub fn print_tools_json(tools: &[(String, u64)], outcomes: &HashMap<String, ToolOutcomes>) {
+    #[derive(serde::Serialize)]
+    struct ToolJson<'a> {
+        tool: &'a str,
+        count: u64,
+        succeeded: u64,
+        failed: u64,
+    }
+
+    let rows: Vec<ToolJson> = tools
+        .iter()
+        .map(|(tool, count)| {
+            let outcome = outcomes.get(tool).copied().unwrap_or_default();
+            ToolJson { tool, count: *count, succeeded: outcome.succeeded, failed: outcome.failed }
+        })
+        .collect();
+    print_json_value(&rows);
+}
+
+pub fn print_commands_json(commands: &[(String, u64)], outcomes: &HashMap<String, ToolOutcomes>) {
+    #[derive(serde::Serialize)]
+    struct CommandJson<'a> {
+        command: &'a str,
+        program: &'a str,
+        count: u64,
+        succeeded: u64,
+        failed: u64,
+    }
+
+    let rows: Vec<CommandJson> = commands
+        .iter()
+        .map(|(command, count)| {
+            let outcome = outcomes.get(command).copied().unwrap_or_default();
+            CommandJson {
+                command,
+                program: program_of(command),
+                count: *count,
+                succeeded: outcome.succeeded,
+                failed: outcome.failed,
+            }
+        })
+        .collect();
+    print_json_value(&rows);
+}
+
+pub fn print_json_value<T: serde::Serialize + ?Sized>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
 }
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::OnceLock;
+
+/// Lines added and removed by one tool call, attributed to a file when known
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditContribution {
+    pub file_path: Option<String>,
+    pub added: u64,
+    pub removed: u64,
+    /// The input doesn't show the whole change, so the counts are a lower bound
+    pub partial: bool,
+}
+
+/// Turns a tool call's input into line contributions
+pub trait ContributionExtractor: Send + Sync {
+    fn extract(&self, input: &Value) -> Vec<EditContribution>;
+}
+
+/// Input shapes the built-in editing tools use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKind {
+    /// Whole-file write: every line of the new content is added
+    Write,
+    /// Single string replacement. With `replace_all` the number of occurrences
+    /// isn't in the input, so the edit counts one of them and is marked partial.
+    Edit,
+    /// Array of string replacements on one file, each counted as an Edit
+    MultiEdit,
+    /// Jupyter cell source. The input holds no old source, so lines removed by
+    /// replacing or deleting a cell can't be counted; those edits are marked partial.
+    NotebookEdit,
+}
+
+/// How a replacement's old and new text turn into lines removed and added
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineCounting {
+    /// Lines that differ in a line diff of the two texts
+    #[default]
+    Diff,
+    /// Every old line removed and every new line added
+    Totals,
+}
+
+/// Config entry mapping another tool (e.g. an MCP server's) onto a built-in shape.
+/// Field names default to the built-in tool's.
+#[derive(Debug, Clone, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EditToolMapping {
+    pub tool: String,
+    pub kind: EditKind,
+    pub path_field: Option<String>,
+    /// Write `content`, Edit `new_string` or NotebookEdit `new_source`
+    pub new_field: Option<String>,
+    /// Edit `old_string`
+    pub old_field: Option<String>,
+    /// MultiEdit `edits`
+    pub edits_field: Option<String>,
+}
+
+/// Extractor for one of the built-in shapes with configurable input field names
+struct ShapeExtractor {
+    kind: EditKind,
+    counting: LineCounting,
+    path_field: String,
+    new_field: String,
+    old_field: String,
+    edits_field: String,
+}
+
+struct Registry {
+    extractors: HashMap<String, Box<dyn ContributionExtractor>>,
+    fingerprint: i64,
+}
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// Register the built-in tools plus the configured mappings. Call once at startup.
+pub fn init(mappings: &[EditToolMapping], counting: LineCounting) {
+    let _ = REGISTRY.set(Registry::new(mappings, counting));
+}
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(|| Registry::new(&[], LineCounting::default()))
+}
+
+/// Line contributions of a tool call; tools without an extractor contribute nothing
+pub fn extract(tool: &str, input: &Value) -> Vec<EditContribution> {
+    match registry().extractors.get(tool) {
+        Some(extractor) => extractor.extract(input),
+        None => Vec::new(),
+    }
+}
+
+/// Identifies the configured mappings and line counting, so parses made under different
+/// ones aren't reused
+pub fn fingerprint() -> i64 {
+    registry().fingerprint
+}
+
+impl Registry {
+    fn new(mappings: &[EditToolMapping], counting: LineCounting) -> Self {
+        let mut extractors: HashMap<String, Box<dyn ContributionExtractor>> = HashMap::new();
+        for (tool, kind) in [
+            ("Write", EditKind::Write),
+            ("Edit", EditKind::Edit),
+            ("MultiEdit", EditKind::MultiEdit),
+            ("NotebookEdit", EditKind::NotebookEdit),
+        ] {
+            extractors.insert(tool.to_string(), Box::new(ShapeExtractor::builtin(kind, counting)));
+        }
+        for m in mappings {
+            extractors.insert(m.tool.clone(), Box::new(ShapeExtractor::mapped(m, counting)));
+        }
+
+        let mut hasher = DefaultHasher::new();
+        mappings.hash(&mut hasher);
+        counting.hash(&mut hasher);
+        Self { extractors, fingerprint: hasher.finish() as i64 }
+    }
+}
+
+impl ShapeExtractor {
+    fn builtin(kind: EditKind, counting: LineCounting) -> Self {
+        let (path, new) = match kind {
+            EditKind::Write => ("file_path", "content"),
+            EditKind::Edit | EditKind::MultiEdit => ("file_path", "new_string"),
+            EditKind::NotebookEdit => ("notebook_path", "new_source"),
+        };
+        Self {
+            kind,
+            counting,
+            path_field: path.to_string(),
+            new_field: new.to_string(),
+            old_field: "old_string".to_string(),
+            edits_field: "edits".to_string(),
+        }
+    }
+
+    fn mapped(m: &EditToolMapping, counting: LineCounting) -> Self {
+        let mut shape = Self::builtin(m.kind, counting);
+        if let Some(ref f) = m.path_field {
+            shape.path_field = f.clone();
+        }
+        if let Some(ref f) = m.new_field {
+            shape.new_field = f.clone();
+        }
+        if let Some(ref f) = m.old_field {
+            shape.old_field = f.clone();
+        }
+        if let Some(ref f) = m.edits_field {
+            shape.edits_field = f.clone();
+        }
+        shape
+    }
+
+    fn replacement(&self, value: &Value) -> (u64, u64) {
+        let old = value.get(&self.old_field).and_then(|v| v.as_str()).unwrap_or("");
+        let new = value.get(&self.new_field).and_then(|v| v.as_str()).unwrap_or("");
+        match self.counting {
+            LineCounting::Diff => diff_lines(old, new),
+            LineCounting::Totals => (count_lines(new), count_lines(old)),
+        }
+    }
+}
+
+impl ContributionExtractor for ShapeExtractor {
+    fn extract(&self, input: &Value) -> Vec<EditContribution> {
+        let file_path = input
+            .get(&self.path_field)
+            .and_then(|f| f.as_str())
+            .map(String::from);
+
+        let mut partial = false;
+        let (added, removed) = match self.kind {
+            EditKind::Write => match input.get(&self.new_field).and_then(|c| c.as_str()) {
+                Some(content) => (content.lines().count() as u64, 0),
+                None => return Vec::new(),
+            },
+            EditKind::Edit => {
+                partial = replaces_all(input);
+                self.replacement(input)
+            }
+            EditKind::MultiEdit => input
+                .get(&self.edits_field)
+                .and_then(|e| e.as_array())
+                .map(|edits| {
+                    partial = edits.iter().any(replaces_all);
+                    edits
+                        .iter()
+                        .map(|e| self.replacement(e))
+                        .fold((0, 0), |(a, r), (ea, er)| (a + ea, r + er))
+                })
+                .unwrap_or((0, 0)),
+            EditKind::NotebookEdit => {
+                let mode = input.get("edit_mode").and_then(|m| m.as_str()).unwrap_or("replace");
+                partial = mode != "insert";
+                if mode == "delete" {
+                    (0, 0)
+                } else {
+                    let source = input.get(&self.new_field).and_then(|s| s.as_str()).unwrap_or("");
+                    (count_lines(source), 0)
+                }
+            }
+        };
+
+        vec![EditContribution { file_path, added, removed, partial }]
+    }
+}
+
+fn replaces_all(value: &Value) -> bool {
+    value.get("replace_all").and_then(|r| r.as_bool()).unwrap_or(false)
+}
+
+fn count_lines(s: &str) -> u64 {
+    if s.is_empty() {
+        0
+    } else {
+        s.lines().count() as u64
+    }
+}
+
+/// Lines added and removed by a line diff from `old` to `new`
+fn diff_lines(old: &str, new: &str) -> (u64, u64) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let (mut added, mut removed) = (0, 0);
+    for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag != DiffTag::Equal {
+            removed += old_range.len() as u64;
+            added += new_range.len() as u64;
+        }
+    }
+    (added, removed)
+}
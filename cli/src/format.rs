@@ -1,4 +1,26 @@
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Extended-thinking volume, e.g. `12 turns · 34.5K chars (avg 2.9K)`
+pub fn format_thinking(turns: u64, chars: u64) -> String {
+    if turns == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{} turns · {} chars (avg {})",
+        format_number(turns),
+        format_number(chars),
+        format_number(chars / turns)
+    )
+}
+
+/// Commits produced and the spend behind each, e.g. `4 · $0.52 per commit`
+pub fn format_commits(count: usize, cost_per_commit: Option<f64>) -> String {
+    match cost_per_commit {
+        Some(cost) => format!("{} · {} per commit", count, format_cost(cost)),
+        None => "-".to_string(),
+    }
+}
 
 pub fn format_number(n: u64) -> String {
     if n >= 1_000_000 {
@@ -72,38 +94,25 @@ pub fn format_date(date: &str) -> String {
     }
 }
 
+/// Cut `s` to at most `max` bytes, on a char boundary, marking the cut with "..."
 pub fn truncate(s: &str, max: usize) -> String {
     if s.len() > max {
-        format!("{}...", &s[..max])
+        format!("{}...", &s[..s.floor_char_boundary(max)])
     } else {
         s.to_string()
     }
 }
 
-// API pricing per million tokens: [input, output, cache_read]
-fn model_pricing(model: &str) -> (f64, f64, f64) {
-    let m = model.to_lowercase();
-    if m.contains("opus") {
-        (15.0, 75.0, 1.5)
-    } else if m.contains("haiku") {
-        (0.8, 4.0, 0.08)
+/// A 0..1 share as a whole percentage, keeping small non-zero shares visible
+pub fn format_percent(share: f64) -> String {
+    let pct = share * 100.0;
+    if pct > 0.0 && pct < 1.0 {
+        "<1%".to_string()
     } else {
-        // Default to sonnet
-        (3.0, 15.0, 0.3)
+        format!("{:.0}%", pct)
     }
 }
 
-pub fn estimate_cost(model: &str, input_tokens: u64, output_tokens: u64, cache_read_tokens: u64) -> f64 {
-    let (input_rate, output_rate, cache_rate) = model_pricing(model);
-    let non_cache_input = if input_tokens > cache_read_tokens {
-        input_tokens - cache_read_tokens
-    } else {
-        0
-    };
-    (non_cache_input as f64 * input_rate + output_tokens as f64 * output_rate + cache_read_tokens as f64 * cache_rate)
-        / 1_000_000.0
-}
-
 pub fn format_cost(cost: f64) -> String {
     if cost < 0.01 {
         "<$0.01".to_string()
@@ -112,6 +121,21 @@ pub fn format_cost(cost: f64) -> String {
     }
 }
 
+/// Per-model costs, most expensive first, e.g. "Opus 4.1 $1.20 · Haiku 3.5 $0.04"
+pub fn format_cost_breakdown(cost_by_model: &HashMap<String, f64>) -> String {
+    let mut models: Vec<(&String, &f64)> = cost_by_model.iter().collect();
+    models.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal));
+    models
+        .into_iter()
+        .map(|(model, cost)| {
+            let name = short_model(model);
+            let name = if name.is_empty() { model.clone() } else { name };
+            format!("{} {}", name, format_cost(*cost))
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
 pub fn short_model(model: &str) -> String {
     if model.is_empty() {
         return String::new();
@@ -129,7 +153,7 @@ pub fn short_model(model: &str) -> String {
             );
             let rest = &m[idx + family.len()..];
             // Try to extract version numbers
-            let parts: Vec<&str> = rest.split(|c: char| c == '-' || c == '_')
+            let parts: Vec<&str> = rest.split(['-', '_'])
                 .filter(|s| !s.is_empty())
                 .collect();
 
@@ -0,0 +1,225 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::models::{GitCommit, ParsedSession, ProjectSummary};
+
+/// Commits made this long after a session's last activity still count as its work
+const COMMIT_GRACE_MINUTES: i64 = 30;
+
+/// Field and record separators in the `git log` format
+const FIELD_SEP: char = '\x1f';
+const RECORD_SEP: char = '\x1e';
+
+/// Repository of each project directory, or `None` outside one; looked up once per run
+static REPOS: OnceLock<Mutex<HashMap<String, Option<Repo>>>> = OnceLock::new();
+
+/// Commits read from each repository, by top-level path
+static LOGS: OnceLock<Mutex<HashMap<PathBuf, CommitLog>>> = OnceLock::new();
+
+#[derive(Clone)]
+struct Repo {
+    root: PathBuf,
+    /// Where this worktree's HEAD lives, and where the refs shared by all worktrees live
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+}
+
+/// Commits since `since`, valid while no ref has changed since `refs_modified`
+struct CommitLog {
+    refs_modified: Option<SystemTime>,
+    since: DateTime<Utc>,
+    commits: Vec<GitCommit>,
+}
+
+/// Attach commits from the project's repository to the sessions that produced them.
+/// A commit belongs to the session active when it was made that touched the most of
+/// its files. A commit without files (e.g. `--allow-empty`) goes to an active session
+/// that ran `git commit` itself. Projects outside a repository are left alone.
+pub fn link_commits(project: &mut ProjectSummary) {
+    let spans: Vec<Option<(DateTime<Utc>, DateTime<Utc>)>> =
+        project.sessions.iter().map(session_span).collect();
+    // Commits after the last session are read too, so a growing session doesn't miss the cache
+    let Some(since) = spans.iter().flatten().map(|(start, _)| *start).min() else {
+        return;
+    };
+    if project.path.is_empty() || !Path::new(&project.path).is_dir() {
+        return;
+    }
+    let Some(repo) = repo_of(&project.path) else {
+        return;
+    };
+    let Ok(commits) = commits_since(&repo, since) else {
+        return;
+    };
+
+    // Tool inputs name files as the session saw them; git reports them with symlinks resolved
+    let edited: Vec<HashSet<PathBuf>> = project
+        .sessions
+        .iter()
+        .map(|s| s.file_contributions.keys().map(|f| resolve(Path::new(f))).collect())
+        .collect();
+
+    for commit in commits {
+        let Ok(made_at) = DateTime::parse_from_rfc3339(&commit.timestamp) else {
+            continue;
+        };
+        let made_at = made_at.with_timezone(&Utc);
+
+        let best = project
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| spans[*i].is_some_and(|(start, end)| start <= made_at && made_at <= end))
+            .map(|(i, s)| (i, s, shared_files(&edited[i], &commit)))
+            .filter(|(_, s, shared)| *shared > 0 || (commit.files.is_empty() && ran_git_commit(s)))
+            .max_by(|a, b| a.2.cmp(&b.2).then_with(|| a.1.started_at.cmp(&b.1.started_at)))
+            .map(|(i, _, _)| i);
+        if let Some(i) = best {
+            project.sessions[i].commits.push(commit);
+            project.commit_count += 1;
+        }
+    }
+}
+
+/// When a session could have committed: from its start until the grace period after it ended
+fn session_span(session: &ParsedSession) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = DateTime::parse_from_rfc3339(&session.started_at).ok()?;
+    let end = DateTime::parse_from_rfc3339(&session.last_active).ok()?;
+    Some((
+        start.with_timezone(&Utc),
+        end.with_timezone(&Utc) + Duration::minutes(COMMIT_GRACE_MINUTES),
+    ))
+}
+
+fn shared_files(edited: &HashSet<PathBuf>, commit: &GitCommit) -> usize {
+    commit
+        .files
+        .iter()
+        .filter(|f| edited.contains(Path::new(f)))
+        .count()
+}
+
+/// A path with symlinks resolved; for a file since deleted, its directory's
+fn resolve(path: &Path) -> PathBuf {
+    if let Ok(resolved) = fs::canonicalize(path) {
+        return resolved;
+    }
+    match (path.parent().and_then(|d| fs::canonicalize(d).ok()), path.file_name()) {
+        (Some(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn ran_git_commit(session: &ParsedSession) -> bool {
+    session.command_usage.contains_key("git commit")
+}
+
+/// The repository a project directory is in, asking git only the first time
+fn repo_of(path: &str) -> Option<Repo> {
+    let repos = REPOS.get_or_init(Default::default);
+    if let Some(repo) = repos.lock().unwrap().get(path) {
+        return repo.clone();
+    }
+    let repo = find_repo(path).ok();
+    repos.lock().unwrap().insert(path.to_string(), repo.clone());
+    repo
+}
+
+fn find_repo(path: &str) -> Result<Repo> {
+    let out = git(path, &["rev-parse", "--show-toplevel", "--absolute-git-dir", "--git-common-dir"])?;
+    let mut lines = out.lines();
+    let mut next = || lines.next().map(PathBuf::from).context("unexpected git rev-parse output");
+    let root = next()?;
+    let git_dir = next()?;
+    // Relative to the directory git ran in
+    let common_dir = Path::new(path).join(next()?);
+    Ok(Repo { root, git_dir, common_dir })
+}
+
+/// Latest change to HEAD or any ref. New commits only reach `git log --all` by moving one.
+fn refs_modified(repo: &Repo) -> Option<SystemTime> {
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    let mut latest = modified(&repo.git_dir.join("HEAD")).max(modified(&repo.common_dir.join("packed-refs")));
+    let mut dirs = vec![repo.common_dir.join("refs")];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                dirs.push(path);
+            } else {
+                latest = latest.max(modified(&path));
+            }
+        }
+    }
+    latest
+}
+
+/// Commits since `since`, reusing the last read while the refs are unchanged and it
+/// reaches back far enough
+fn commits_since(repo: &Repo, since: DateTime<Utc>) -> Result<Vec<GitCommit>> {
+    let refs_modified = refs_modified(repo);
+    let logs = LOGS.get_or_init(Default::default);
+    if let Some(log) = logs.lock().unwrap().get(&repo.root) {
+        if log.refs_modified == refs_modified && log.since <= since {
+            return Ok(log.commits.clone());
+        }
+    }
+    let commits = read_commits(repo, since)?;
+    logs.lock().unwrap().insert(
+        repo.root.clone(),
+        CommitLog { refs_modified, since, commits: commits.clone() },
+    );
+    Ok(commits)
+}
+
+/// Non-merge commits on any branch since `since`, oldest first, with their files as
+/// absolute paths
+fn read_commits(repo: &Repo, since: DateTime<Utc>) -> Result<Vec<GitCommit>> {
+    let root = repo.root.as_path();
+    let log = git(
+        &root.to_string_lossy(),
+        &[
+            "log",
+            "--all",
+            "--no-merges",
+            "--reverse",
+            &format!("--since={}", since.to_rfc3339()),
+            &format!("--format={}%H{}%cI{}%s", RECORD_SEP, FIELD_SEP, FIELD_SEP),
+            "--name-only",
+        ],
+    )?;
+
+    let commits = log
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.splitn(3, FIELD_SEP);
+            let hash = fields.next()?.to_string();
+            let timestamp = fields.next()?.to_string();
+            let subject = fields.next().unwrap_or_default().to_string();
+            let files = lines
+                .filter(|l| !l.is_empty())
+                .map(|l| root.join(l).to_string_lossy().into_owned())
+                .collect();
+            Some(GitCommit { hash, timestamp, subject, files })
+        })
+        .collect();
+    Ok(commits)
+}
+
+fn git(dir: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        bail!("git {} failed in {}", args.join(" "), dir);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
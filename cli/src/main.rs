@@ -1,15 +1,27 @@
+mod cache;
+mod commands;
+mod config;
 mod cursor_parser;
 mod cursor_scanner;
+mod dedup;
 mod display;
+mod edits;
 mod format;
+mod git;
+mod markdown;
 mod metrics;
 mod models;
 mod parser;
+mod pricing;
 mod scanner;
+mod search;
+mod shell;
 mod theme;
 mod tui_app;
 mod tui_events;
 mod tui_ui;
+mod watcher;
+mod window;
 
 use anyhow::Result;
 use clap::Parser;
@@ -20,34 +32,103 @@ use crossterm::{
 use ratatui::{backend::CrosstermBackend, Terminal};
 use rayon::prelude::*;
 use std::io;
+use std::path::PathBuf;
 use std::sync::mpsc;
 
+use crate::cache::{CachedParse, ParseCache, SessionMemo};
+use crate::commands::Command;
 use crate::display::{print_cli_table, print_json};
 use crate::metrics::{build_project_summaries, compute_global_metrics};
 use crate::models::{DataSource, ParsedSession};
 use crate::scanner::{scan_all_projects, scan_claude_projects};
 use crate::tui_app::App;
+use crate::window::{TimeWindow, WindowArgs};
 
 #[derive(Parser)]
 #[command(name = "ct", about = "Claude Tracker — analyze Claude Code & Cursor usage")]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Print table output instead of interactive TUI
     #[arg(long)]
     cli: bool,
 
     /// Output as JSON
-    #[arg(long)]
+    #[arg(long, global = true)]
     json: bool,
+
+    #[command(flatten)]
+    window: WindowArgs,
+
+    /// Ignore the on-disk parse cache and reparse every session
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Extra Claude data root (a dir containing `projects/`); repeatable
+    #[arg(long, global = true, value_name = "PATH")]
+    claude_dir: Vec<PathBuf>,
+
+    /// Cursor data directory (the `User` dir or the Cursor dir above it); repeatable
+    #[arg(long, global = true, value_name = "PATH", env = cursor_scanner::CURSOR_DIR_ENV)]
+    cursor_dir: Vec<PathBuf>,
+
+    /// Don't read project repositories to link sessions to commits
+    #[arg(long, global = true)]
+    no_git: bool,
 }
 
 use crate::tui_app::LoadMessage;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+/// Options shared by every data load
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    pub window: TimeWindow,
+    pub use_cache: bool,
+    /// Claude data roots to scan
+    pub claude_dirs: Vec<PathBuf>,
+    /// Cursor `User` directories to scan
+    pub cursor_dirs: Vec<PathBuf>,
+    /// Link sessions to commits in the project repositories
+    pub git: bool,
+}
 
 /// Load all data (scan + parse + aggregate), optionally sending progress
 fn load_data(
     progress: Option<mpsc::Sender<LoadMessage>>,
+    opts: &LoadOptions,
+) -> Result<(Vec<crate::models::ProjectSummary>, crate::models::GlobalMetrics)> {
+    let mut memo = SessionMemo::default();
+    let (projects, metrics) = load_data_with(progress, opts, &mut memo)?;
+    warn_unpriced(&metrics);
+    Ok((projects, metrics))
+}
+
+/// Tell the user which models were counted at $0 for lack of a price
+fn warn_unpriced(metrics: &crate::models::GlobalMetrics) {
+    if metrics.unpriced_models.is_empty() {
+        return;
+    }
+    let [toml_path, json_path] = pricing::pricing_paths();
+    eprintln!(
+        "warning: no price for {}; their usage is counted as $0. Add them to {} or {}",
+        metrics.unpriced_models.join(", "),
+        toml_path.display(),
+        json_path.display(),
+    );
+}
+
+/// Load all data, reusing parse results in `memo` for files that have not changed
+/// and folding in appended lines for files that grew.
+fn load_data_with(
+    progress: Option<mpsc::Sender<LoadMessage>>,
+    opts: &LoadOptions,
+    memo: &mut SessionMemo,
 ) -> Result<(Vec<crate::models::ProjectSummary>, crate::models::GlobalMetrics)> {
+    let window = &opts.window;
     let send = |msg: &str| {
         if let Some(ref tx) = progress {
             let _ = tx.send(LoadMessage::Progress(msg.to_string()));
@@ -55,15 +136,36 @@ fn load_data(
     };
 
     send("Scanning Claude projects...");
-    let claude_projects = scan_claude_projects()?;
+    let claude_projects = scan_claude_projects(&opts.claude_dirs)?;
 
     send("Scanning Cursor workspaces...");
-    let cursor_projects = cursor_scanner::scan_cursor_projects().unwrap_or_default();
+    let cursor_projects =
+        cursor_scanner::scan_cursor_projects(&opts.cursor_dirs).unwrap_or_default();
 
     send("Merging projects...");
     let scanned = scan_all_projects(claude_projects, cursor_projects);
     let total = scanned.len();
 
+    // Cached parses are unclipped, so the on-disk cache only applies to all-time loads
+    let mut cache = if opts.use_cache && window.is_unbounded() {
+        ParseCache::open().ok()
+    } else {
+        None
+    };
+    if memo.is_empty() {
+        if let Some(ref c) = cache {
+            send("Reading cache...");
+            *memo = c.load_all();
+        }
+    }
+
+    let memo_lock = Mutex::new(std::mem::take(memo));
+    let changed_keys: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+    let seen_keys: HashSet<String> = scanned
+        .iter()
+        .flat_map(|p| p.session_files.iter().map(cache::cache_key))
+        .collect();
+
     let counter = Arc::new(AtomicUsize::new(0));
     let progress_tx = progress.clone();
 
@@ -76,7 +178,7 @@ fn load_data(
 
             // Report progress
             let n = counter.fetch_add(1, Ordering::Relaxed) + 1;
-            let name = dir.split('/').last().unwrap_or(&project_id);
+            let name = dir.split('/').next_back().unwrap_or(&project_id);
             if let Some(ref tx) = progress_tx {
                 let _ = tx.send(LoadMessage::Progress(format!(
                     "Parsing: {} ({}/{})",
@@ -87,31 +189,108 @@ fn load_data(
             let sessions: Vec<ParsedSession> = project
                 .session_files
                 .par_iter()
-                .filter_map(|sf| match sf.source {
-                    DataSource::Claude => {
-                        parser::parse_session_file(&sf.path, &sf.id, &project_id).ok()
+                .filter(|sf| window.may_contain_file(sf.modified))
+                .filter_map(|sf| {
+                    let prior = memo_lock.lock().unwrap().take(sf);
+                    let unchanged = prior.as_ref().is_some_and(|e| e.is_unchanged(sf));
+
+                    let (mut session, parse) = match sf.source {
+                        DataSource::Claude => {
+                            // Resume from the remembered offset when the file only grew
+                            let mut state =
+                                prior.and_then(|e| e.into_state(sf)).unwrap_or_default();
+                            if !unchanged {
+                                state.update(&sf.path, window).ok()?;
+                            }
+                            let mut session = state.to_session(&sf.id, &project_id);
+                            session.parent_session = sf.parent_session.clone();
+                            (session, CachedParse::Claude(Box::new(state)))
+                        }
+                        DataSource::Cursor => {
+                            let mut session = match prior.and_then(|e| e.into_session(sf)) {
+                                Some(mut s) => {
+                                    // The pricing table may have changed since it was cached
+                                    cursor_parser::price_session(&mut s);
+                                    s
+                                }
+                                None => cursor_parser::parse_cursor_session(
+                                    &sf.path,
+                                    &sf.id,
+                                    &project_id,
+                                    window,
+                                )
+                                .ok()?,
+                            };
+                            session.project_id = project_id.clone();
+                            (session.clone(), CachedParse::Cursor(Box::new(session)))
+                        }
+                    };
+
+                    if !unchanged {
+                        changed_keys.lock().unwrap().insert(cache::cache_key(sf));
                     }
-                    DataSource::Cursor => {
-                        cursor_parser::parse_cursor_session(&sf.path, &sf.id, &project_id).ok()
+                    memo_lock.lock().unwrap().put(sf, parse);
+
+                    // Sidechains folded out of a transcript live in the same file
+                    session.source_path = sf.path.clone();
+                    for agent in &mut session.agents {
+                        agent.source_path = sf.path.clone();
                     }
+                    Some(session)
                 })
                 .collect();
             (project_id, dir, sessions, sources)
         })
         .collect();
 
+    *memo = memo_lock.into_inner().unwrap();
+    memo.retain_keys(&seen_keys);
+
+    if let Some(ref mut cache) = cache {
+        send("Updating cache...");
+        let _ = cache.store(memo, &changed_keys.into_inner().unwrap());
+    }
+
     send("Building metrics...");
-    let projects = build_project_summaries(project_sessions);
+    let mut projects = build_project_summaries(project_sessions, window);
+    if opts.git {
+        send("Reading git history...");
+        projects.par_iter_mut().for_each(git::link_commits);
+    }
     let metrics = compute_global_metrics(&projects);
     Ok((projects, metrics))
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
+    let config = config::load_config()?;
+    pricing::init()?;
+    edits::init(&config.edit_tools, config.line_counting);
+
+    let mut claude_dirs = config.claude_dirs;
+    claude_dirs.extend(args.claude_dir);
+    let cursor_dirs = if args.cursor_dir.is_empty() {
+        config.cursor_dirs
+    } else {
+        args.cursor_dir
+    };
+
+    let opts = LoadOptions {
+        window: args.window.to_window()?,
+        use_cache: !args.no_cache,
+        claude_dirs: scanner::claude_roots(&claude_dirs),
+        cursor_dirs: cursor_scanner::cursor_user_dirs(&cursor_dirs),
+        git: !args.no_git,
+    };
+
+    match args.command {
+        None | Some(Command::Tui) => {}
+        Some(command) => return commands::run(command, args.json, &opts),
+    }
 
     // Non-TUI modes: load synchronously
     if args.json || args.cli {
-        let (projects, metrics) = load_data(None)?;
+        let (projects, metrics) = load_data(None, &opts)?;
         if args.json {
             print_json(&projects, &metrics);
         } else {
@@ -121,10 +300,10 @@ fn main() -> Result<()> {
     }
 
     // TUI mode: show immediately, load in background
-    run_tui()
+    run_tui(opts)
 }
 
-fn run_tui() -> Result<()> {
+fn run_tui(opts: LoadOptions) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -132,16 +311,32 @@ fn run_tui() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Spawn background data loading with progress
+    // Spawn background data loading with progress, then keep refreshing on file changes
     let (tx, rx) = mpsc::channel();
+    let window = opts.window;
     std::thread::spawn(move || {
-        let progress_tx = tx.clone();
-        if let Ok((projects, metrics)) = load_data(Some(progress_tx)) {
-            let _ = tx.send(LoadMessage::Done(projects, metrics));
+        let mut memo = SessionMemo::default();
+        match load_data_with(Some(tx.clone()), &opts, &mut memo) {
+            Ok((projects, metrics)) => {
+                if tx.send(LoadMessage::Done(projects, metrics)).is_err() {
+                    return;
+                }
+            }
+            Err(_) => return,
         }
+
+        let _ = watcher::watch_for_changes(&opts.claude_dirs, &opts.cursor_dirs, |change| {
+            if change.cursor {
+                memo.invalidate_cursor();
+            }
+            match load_data_with(None, &opts, &mut memo) {
+                Ok((projects, metrics)) => tx.send(LoadMessage::Updated(projects, metrics)).is_ok(),
+                Err(_) => true,
+            }
+        });
     });
 
-    let mut app = App::loading(rx);
+    let mut app = App::loading(rx, window);
 
     // Main loop
     loop {
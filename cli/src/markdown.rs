@@ -0,0 +1,404 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::sync::OnceLock;
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
+
+use crate::theme::ThemeColors;
+
+/// Bundled grammars, loaded on first use
+static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+
+/// Picks one colour out of the theme
+type ThemeColor = fn(&ThemeColors) -> Color;
+
+/// Scope prefixes and the theme colour they take, checked in order
+static SCOPE_COLORS: OnceLock<Vec<(Scope, ThemeColor)>> = OnceLock::new();
+
+/// Render Markdown as styled lines: headings, emphasis, lists, quotes, and fenced
+/// code highlighted by its language. Source line breaks are kept; nothing is wrapped.
+pub fn render(text: &str, tc: &ThemeColors) -> Vec<Line<'static>> {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut r = Renderer::new(tc);
+    for event in Parser::new_ext(text, options) {
+        r.event(event);
+    }
+    r.finish()
+}
+
+struct Renderer<'t> {
+    tc: &'t ThemeColors,
+    lines: Vec<Line<'static>>,
+    spans: Vec<Span<'static>>,
+    /// Inline styles in effect, innermost last
+    styles: Vec<Style>,
+    /// Open lists, with the next number of an ordered one
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    /// Language and text of the code block being read
+    code: Option<(String, String)>,
+    /// Inside inline XML-like tags, whose markup is hidden and content coloured
+    xml_depth: usize,
+}
+
+impl<'t> Renderer<'t> {
+    fn new(tc: &'t ThemeColors) -> Self {
+        Renderer {
+            tc,
+            lines: Vec::new(),
+            spans: Vec::new(),
+            styles: vec![Style::default().fg(tc.fg)],
+            lists: Vec::new(),
+            quotes: 0,
+            code: None,
+            xml_depth: 0,
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match self.code {
+                Some((_, ref mut code)) => code.push_str(&text),
+                None => {
+                    for (i, line) in text.split('\n').enumerate() {
+                        if i > 0 {
+                            self.flush();
+                        }
+                        self.push(line.to_string(), self.style());
+                    }
+                }
+            },
+            Event::Code(code) => {
+                let style = Style::default().fg(self.tc.warning);
+                self.push(code.to_string(), style);
+            }
+            Event::InlineHtml(tag) => {
+                if tag.starts_with("</") {
+                    self.xml_depth = self.xml_depth.saturating_sub(1);
+                } else if !tag.ends_with("/>") {
+                    self.xml_depth += 1;
+                }
+            }
+            Event::Html(html) => {
+                for line in html.lines() {
+                    self.flush();
+                    for span in style_xml_content(line, self.tc.fg, self.tc.xml_tag) {
+                        self.push(span.content.into_owned(), span.style);
+                    }
+                }
+                self.flush();
+            }
+            Event::SoftBreak | Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                self.push("─".repeat(40), Style::default().fg(self.tc.border));
+                self.block_end();
+            }
+            Event::TaskListMarker(done) => {
+                let style = Style::default().fg(if done { self.tc.success } else { self.tc.muted });
+                self.push(if done { "[x] " } else { "[ ] " }.to_string(), style);
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        let tc = self.tc;
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush();
+                let style = match level {
+                    HeadingLevel::H1 => Style::default()
+                        .fg(tc.title)
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    HeadingLevel::H2 => Style::default().fg(tc.title).add_modifier(Modifier::BOLD),
+                    _ => Style::default().fg(tc.accent).add_modifier(Modifier::BOLD),
+                };
+                self.styles.push(style);
+            }
+            Tag::Emphasis => self.styles.push(self.style().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.styles.push(self.style().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.styles.push(self.style().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { .. } => self
+                .styles
+                .push(self.style().fg(tc.accent).add_modifier(Modifier::UNDERLINED)),
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quotes += 1;
+                self.styles.push(self.style().fg(tc.muted).add_modifier(Modifier::ITALIC));
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split([' ', ',', '{']).next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                self.spans.push(Span::raw(self.quote_prefix()));
+                self.spans.push(Span::styled(format!("{}{}", indent, bullet), Style::default().fg(tc.accent)));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush();
+                if self.lists.is_empty() {
+                    self.block_end();
+                }
+            }
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.flush();
+                self.block_end();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.styles.pop();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.styles.pop();
+                self.flush();
+                self.quotes = self.quotes.saturating_sub(1);
+                self.block_end();
+            }
+            TagEnd::CodeBlock => {
+                if let Some((lang, code)) = self.code.take() {
+                    self.code_block(&lang, &code);
+                }
+                self.block_end();
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block_end();
+                }
+            }
+            TagEnd::Item => self.flush(),
+            _ => {}
+        }
+    }
+
+    fn style(&self) -> Style {
+        let style = *self.styles.last().unwrap_or(&Style::default());
+        if self.xml_depth > 0 {
+            style.fg(self.tc.xml_tag)
+        } else {
+            style
+        }
+    }
+
+    fn quote_prefix(&self) -> String {
+        "▎ ".repeat(self.quotes)
+    }
+
+    /// Add text to the current line, starting it with the quote and list indent
+    fn push(&mut self, text: String, style: Style) {
+        if self.spans.is_empty() {
+            let prefix = format!("{}{}", self.quote_prefix(), "  ".repeat(self.lists.len()));
+            if !prefix.is_empty() {
+                self.spans.push(Span::styled(prefix, Style::default().fg(self.tc.muted)));
+            }
+        }
+        if !text.is_empty() {
+            self.spans.push(Span::styled(text, style));
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.spans.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+    }
+
+    /// Separate a finished block from the next one
+    fn block_end(&mut self) {
+        self.flush();
+        if self.lines.last().is_some_and(|l| l.width() > 0) {
+            self.lines.push(Line::from(""));
+        }
+    }
+
+    fn code_block(&mut self, lang: &str, code: &str) {
+        let gutter = Style::default().fg(self.tc.border);
+        if !lang.is_empty() {
+            self.push("╭ ".to_string(), gutter);
+            self.push(lang.to_string(), Style::default().fg(self.tc.muted).add_modifier(Modifier::ITALIC));
+            self.flush();
+        }
+        for spans in highlight(lang, code, self.tc) {
+            self.push("│ ".to_string(), gutter);
+            self.spans.extend(spans);
+            self.flush();
+        }
+    }
+
+    fn finish(mut self) -> Vec<Line<'static>> {
+        if let Some((lang, code)) = self.code.take() {
+            self.code_block(&lang, &code);
+        }
+        self.flush();
+        while self.lines.last().is_some_and(|l| l.width() == 0) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+/// Spans for each line of `code`, coloured by scope when the language is known
+fn highlight(lang: &str, code: &str, tc: &ThemeColors) -> Vec<Vec<Span<'static>>> {
+    let plain = |line: &str| vec![Span::styled(line.to_string(), Style::default().fg(tc.fg))];
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let syntax = match lang {
+        "" => None,
+        _ => syntaxes.find_syntax_by_token(lang),
+    };
+    let Some(syntax) = syntax else {
+        return code.lines().map(plain).collect();
+    };
+
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut lines = Vec::new();
+    for line in code.split_inclusive('\n') {
+        let Ok(ops) = state.parse_line(line, syntaxes) else {
+            lines.push(plain(line.trim_end_matches('\n')));
+            continue;
+        };
+        let text = line.trim_end_matches('\n');
+        let mut spans = Vec::new();
+        let mut pos = 0;
+        for (at, op) in ops {
+            let at = at.min(text.len());
+            if at > pos {
+                spans.push(Span::styled(text[pos..at].to_string(), scope_style(&stack, tc)));
+                pos = at;
+            }
+            let _ = stack.apply(&op);
+        }
+        if pos < text.len() {
+            spans.push(Span::styled(text[pos..].to_string(), scope_style(&stack, tc)));
+        }
+        lines.push(spans);
+    }
+    lines
+}
+
+/// Theme colour of the innermost scope with one
+fn scope_style(stack: &ScopeStack, tc: &ThemeColors) -> Style {
+    let classes = SCOPE_COLORS.get_or_init(|| {
+        let classes: [(&str, ThemeColor); 13] = [
+            ("comment", |tc| tc.muted),
+            ("string", |tc| tc.success),
+            ("constant.numeric", |tc| tc.warning),
+            ("constant.language", |tc| tc.warning),
+            ("constant.character", |tc| tc.warning),
+            ("keyword", |tc| tc.accent),
+            ("storage", |tc| tc.accent),
+            ("entity.name.function", |tc| tc.token_output),
+            ("support.function", |tc| tc.token_output),
+            ("entity.name", |tc| tc.title),
+            ("support.type", |tc| tc.title),
+            ("support.class", |tc| tc.title),
+            ("entity.other.attribute-name", |tc| tc.xml_tag),
+        ];
+        classes
+            .into_iter()
+            .filter_map(|(name, color)| Some((Scope::new(name).ok()?, color)))
+            .collect()
+    });
+
+    let color = stack.as_slice().iter().rev().find_map(|scope| {
+        classes
+            .iter()
+            .find(|(class, _)| class.is_prefix_of(*scope))
+            .map(|(_, color)| color(tc))
+    });
+    let style = Style::default().fg(color.unwrap_or(tc.fg));
+    if color == Some(tc.muted) {
+        style.add_modifier(Modifier::ITALIC)
+    } else {
+        style
+    }
+}
+
+/// Remove XML tag markup but keep the content between tags, colored in `tag_color`.
+/// `<command-message>hello</command-message> world` →
+///   [Span("hello", purple), Span(" world", normal)]
+pub fn style_xml_content<'a>(s: &str, text_color: Color, tag_color: Color) -> Vec<Span<'a>> {
+    let mut spans: Vec<Span<'a>> = Vec::new();
+    let mut current_text = String::new();
+    let mut in_tag_content = false; // true when we're between <tag> and </tag>
+    let mut chars = s.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '<' {
+            // Scan ahead for closing '>'
+            let mut tag = String::from('<');
+            let mut found_close = false;
+            if chars.peek().is_none_or(|c| c.is_whitespace()) {
+                current_text.push(ch);
+                continue;
+            }
+            for next in chars.by_ref() {
+                tag.push(next);
+                if next == '>' {
+                    found_close = true;
+                    break;
+                }
+                if tag.len() > 200 {
+                    break;
+                }
+            }
+            if found_close {
+                // Flush accumulated text before this tag
+                if !current_text.is_empty() {
+                    let color = if in_tag_content { tag_color } else { text_color };
+                    spans.push(Span::styled(current_text.clone(), Style::default().fg(color)));
+                    current_text.clear();
+                }
+                // Check if this is an opening or closing tag
+                let is_closing = tag.starts_with("</");
+                in_tag_content = !is_closing;
+                // Tag markup itself is not rendered
+            } else {
+                current_text.push_str(&tag);
+            }
+        } else {
+            current_text.push(ch);
+        }
+    }
+
+    // Flush remaining text
+    if !current_text.is_empty() {
+        let color = if in_tag_content { tag_color } else { text_color };
+        spans.push(Span::styled(current_text, Style::default().fg(color)));
+    }
+
+    if spans.is_empty() {
+        spans.push(Span::styled("", Style::default()));
+    }
+
+    spans
+}
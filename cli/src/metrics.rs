@@ -1,17 +1,156 @@
 use std::collections::HashMap;
 
-use crate::models::{DataSource, GlobalMetrics, ParsedSession, ProjectSummary, TimelineEntry, TokenTotals};
-use crate::format::estimate_cost;
+use crate::dedup::dedup_sessions;
+use crate::models::{
+    ConversationThread, CostParts, DataSource, DedupStats, GlobalMetrics, ParsedSession, ProjectSummary, TimelineEntry,
+    TokenTotals, ToolOutcomes,
+};
+use crate::window::TimeWindow;
+
+/// Add one per-model cost breakdown into another
+fn add_costs(into: &mut HashMap<String, f64>, from: &HashMap<String, f64>) {
+    for (model, cost) in from {
+        *into.entry(model.clone()).or_insert(0.0) += cost;
+    }
+}
+
+fn add_counts(into: &mut HashMap<String, u64>, from: &HashMap<String, u64>) {
+    for (key, count) in from {
+        *into.entry(key.clone()).or_insert(0) += count;
+    }
+}
+
+fn add_outcomes(into: &mut HashMap<String, ToolOutcomes>, from: &HashMap<String, ToolOutcomes>) {
+    for (tool, outcome) in from {
+        into.entry(tool.clone()).or_default().add(outcome);
+    }
+}
+
+/// Attach subagent transcripts to the sessions they ran under. Agents whose parent
+/// isn't among the sessions stay standalone.
+fn attach_agent_sessions(sessions: Vec<ParsedSession>) -> Vec<ParsedSession> {
+    let (mut agents, mut sessions): (Vec<_>, Vec<_>) =
+        sessions.into_iter().partition(|s| s.parent_session.is_some());
+
+    agents.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    for agent in agents {
+        let parent = agent.parent_session.as_deref().unwrap_or_default();
+        match sessions.iter_mut().find(|s| s.session_id == parent) {
+            Some(_) if agent.messages.is_empty() => {}
+            Some(s) => s.attach_agent(agent),
+            None => sessions.push(agent),
+        }
+    }
+    sessions
+}
+
+/// Link sessions that resume or continue an earlier one into threads, and order the
+/// sessions thread by thread (most recently active first, oldest session first within).
+/// Returns the threads of more than one session.
+fn link_threads(sessions: &mut Vec<ParsedSession>) -> Vec<ConversationThread> {
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+
+    // A resumed file may repeat earlier messages, so a uuid belongs to its earliest session
+    let previous: Vec<Option<usize>> = {
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        for (i, s) in sessions.iter().enumerate() {
+            for m in s.messages.iter().filter(|m| !m.uuid.is_empty()) {
+                owners.entry(m.uuid.as_str()).or_insert(i);
+            }
+        }
+        sessions
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                s.continues_from
+                    .iter()
+                    .filter_map(|uuid| owners.get(uuid.as_str()).copied())
+                    .find(|&j| j < i)
+            })
+            .collect()
+    };
+
+    let mut roots: Vec<usize> = Vec::with_capacity(sessions.len());
+    for (i, prev) in previous.iter().enumerate() {
+        let root = prev.map(|j| roots[j]).unwrap_or(i);
+        roots.push(root);
+    }
+    for i in 0..sessions.len() {
+        sessions[i].previous_session = previous[i].map(|j| sessions[j].session_id.clone());
+        sessions[i].thread_id = sessions[roots[i]].session_id.clone();
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &root) in roots.iter().enumerate() {
+        members.entry(root).or_default().push(i);
+    }
+    let mut groups: Vec<Vec<usize>> = members.into_values().collect();
+    let last_active = |group: &[usize]| {
+        group.iter().map(|&i| sessions[i].last_active.as_str()).max().unwrap_or("")
+    };
+    groups.sort_by(|a, b| last_active(b).cmp(last_active(a)));
+
+    let threads: Vec<ConversationThread> = groups
+        .iter()
+        .filter(|g| g.len() > 1)
+        .map(|g| {
+            let mut thread = ConversationThread {
+                id: sessions[g[0]].session_id.clone(),
+                session_ids: Vec::with_capacity(g.len()),
+                summary: String::new(),
+                message_count: 0,
+                total_tokens: TokenTotals::zero(),
+                cost: 0.0,
+                lines_added: 0,
+                lines_removed: 0,
+                duration_ms: 0.0,
+                started_at: sessions[g[0]].started_at.clone(),
+                last_active: last_active(g).to_string(),
+            };
+            for &i in g {
+                let s = &sessions[i];
+                thread.session_ids.push(s.session_id.clone());
+                if !s.summary.is_empty() {
+                    thread.summary = s.summary.clone();
+                }
+                thread.message_count += s.messages.len();
+                thread.total_tokens.input += s.total_tokens.input;
+                thread.total_tokens.output += s.total_tokens.output;
+                thread.total_tokens.cache_read += s.total_tokens.cache_read;
+                thread.total_tokens.cache_creation += s.total_tokens.cache_creation;
+                thread.cost += s.cost;
+                thread.lines_added += s.lines_added;
+                thread.lines_removed += s.lines_removed;
+                thread.duration_ms += s.duration_ms;
+            }
+            thread
+        })
+        .collect();
+
+    let mut slots: Vec<Option<ParsedSession>> = sessions.drain(..).map(Some).collect();
+    sessions.extend(groups.iter().flatten().filter_map(|&i| slots[i].take()));
+    threads
+}
 
 pub fn build_project_summaries(
     projects: Vec<(String, String, Vec<ParsedSession>, Vec<DataSource>)>,
+    window: &TimeWindow,
 ) -> Vec<ProjectSummary> {
     let mut summaries: Vec<ProjectSummary> = Vec::new();
 
-    for (project_id, project_dir, sessions, sources) in projects {
+    for (project_id, project_dir, mut sessions, mut sources) in projects {
+        dedup_sessions(&mut sessions);
+        let mut sessions = attach_agent_sessions(sessions);
+
+        // Sessions clipped to nothing by the time window don't count
+        if !window.is_unbounded() {
+            sessions.retain(|s| !s.messages.is_empty() || !s.agents.is_empty());
+            sources.retain(|src| sessions.iter().any(|s| s.source == *src));
+        }
         if sessions.is_empty() {
             continue;
         }
+        let threads = link_threads(&mut sessions);
 
         // Derive project name: session cwd > project dir > project_id
         let path = sessions
@@ -27,18 +166,26 @@ pub fn build_project_summaries(
             })
             .unwrap_or_default();
         let name = if !path.is_empty() {
-            path.split('/').last().unwrap_or(&project_id).to_string()
+            path.split('/').next_back().unwrap_or(&project_id).to_string()
         } else {
             project_id.clone()
         };
 
         let mut tokens = TokenTotals::zero();
         let mut tool_usage: HashMap<String, u64> = HashMap::new();
+        let mut tool_outcomes: HashMap<String, ToolOutcomes> = HashMap::new();
+        let mut command_usage: HashMap<String, u64> = HashMap::new();
+        let mut command_outcomes: HashMap<String, ToolOutcomes> = HashMap::new();
         let mut message_count = 0usize;
         let mut lines_added = 0u64;
         let mut lines_removed = 0u64;
         let mut last_active = String::new();
         let mut model = String::new();
+        let mut cost_parts = CostParts::default();
+        let mut cost_by_model: HashMap<String, f64> = HashMap::new();
+        let mut deduped = DedupStats::default();
+        let mut thinking_chars = 0u64;
+        let mut thinking_turns = 0u64;
 
         for s in &sessions {
             tokens.input += s.total_tokens.input;
@@ -48,10 +195,18 @@ pub fn build_project_summaries(
             message_count += s.messages.len();
             lines_added += s.lines_added;
             lines_removed += s.lines_removed;
+            cost_parts.add(&s.cost_parts);
+            add_costs(&mut cost_by_model, &s.cost_by_model);
+            deduped.add(&s.deduped);
+            thinking_chars += s.thinking_chars;
+            thinking_turns += s.thinking_turns;
 
             for (tool, count) in &s.tool_usage {
                 *tool_usage.entry(tool.clone()).or_insert(0) += count;
             }
+            add_outcomes(&mut tool_outcomes, &s.tool_outcomes);
+            add_counts(&mut command_usage, &s.command_usage);
+            add_outcomes(&mut command_outcomes, &s.command_outcomes);
 
             if !s.last_active.is_empty() && s.last_active > last_active {
                 last_active = s.last_active.clone();
@@ -61,13 +216,6 @@ pub fn build_project_summaries(
             }
         }
 
-        let cost = estimate_cost(
-            &model,
-            tokens.input,
-            tokens.output,
-            tokens.cache_read,
-        );
-
         let session_count = sessions.len();
         summaries.push(ProjectSummary {
             id: project_id,
@@ -80,10 +228,20 @@ pub fn build_project_summaries(
             lines_removed,
             last_active,
             tool_usage,
-            cost,
+            tool_outcomes,
+            command_usage,
+            command_outcomes,
+            cost: cost_parts.total(),
+            cost_parts,
+            cost_by_model,
+            deduped,
+            thinking_chars,
+            thinking_turns,
             model,
             sessions,
             sources,
+            threads,
+            commit_count: 0,
         });
     }
 
@@ -95,11 +253,19 @@ pub fn build_project_summaries(
 pub fn compute_global_metrics(projects: &[ProjectSummary]) -> GlobalMetrics {
     let mut tokens = TokenTotals::zero();
     let mut tool_usage: HashMap<String, u64> = HashMap::new();
+    let mut tool_outcomes: HashMap<String, ToolOutcomes> = HashMap::new();
+    let mut command_usage: HashMap<String, u64> = HashMap::new();
+    let mut command_outcomes: HashMap<String, ToolOutcomes> = HashMap::new();
     let mut total_messages = 0usize;
     let mut total_sessions = 0usize;
     let mut lines_added = 0u64;
     let mut lines_removed = 0u64;
-    let mut total_cost = 0.0f64;
+    let mut cost_parts = CostParts::default();
+    let mut cost_by_model: HashMap<String, f64> = HashMap::new();
+    let mut unpriced_models: Vec<String> = Vec::new();
+    let mut deduped = DedupStats::default();
+    let mut thinking_chars = 0u64;
+    let mut thinking_turns = 0u64;
     let mut human_lines = 0u64;
     let mut human_words = 0u64;
     let mut human_chars = 0u64;
@@ -115,14 +281,22 @@ pub fn compute_global_metrics(projects: &[ProjectSummary]) -> GlobalMetrics {
         total_sessions += p.session_count;
         lines_added += p.lines_added;
         lines_removed += p.lines_removed;
-        total_cost += p.cost;
+        cost_parts.add(&p.cost_parts);
+        add_costs(&mut cost_by_model, &p.cost_by_model);
+        deduped.add(&p.deduped);
+        thinking_chars += p.thinking_chars;
+        thinking_turns += p.thinking_turns;
 
         for (tool, count) in &p.tool_usage {
             *tool_usage.entry(tool.clone()).or_insert(0) += count;
         }
+        add_outcomes(&mut tool_outcomes, &p.tool_outcomes);
+        add_counts(&mut command_usage, &p.command_usage);
+        add_outcomes(&mut command_outcomes, &p.command_outcomes);
 
         for s in &p.sessions {
             human_lines += s.human_lines;
+            unpriced_models.extend(s.unpriced_models.iter().cloned());
             human_words += s.human_words;
             human_chars += s.human_chars;
 
@@ -151,6 +325,9 @@ pub fn compute_global_metrics(projects: &[ProjectSummary]) -> GlobalMetrics {
         }
     }
 
+    unpriced_models.sort();
+    unpriced_models.dedup();
+
     let mut timeline: Vec<TimelineEntry> = day_map.into_values().collect();
     timeline.sort_by(|a, b| a.date.cmp(&b.date));
 
@@ -160,10 +337,19 @@ pub fn compute_global_metrics(projects: &[ProjectSummary]) -> GlobalMetrics {
         total_messages,
         total_tokens: tokens,
         tool_usage,
+        tool_outcomes,
+        command_usage,
+        command_outcomes,
         timeline,
         total_lines_added: lines_added,
         total_lines_removed: lines_removed,
-        total_cost,
+        total_cost: cost_parts.total(),
+        cost_parts,
+        cost_by_model,
+        unpriced_models,
+        deduped,
+        thinking_chars,
+        thinking_turns,
         human_lines,
         human_words,
         human_chars,
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::edits::EditContribution;
+
 // --- Data source enum ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum DataSource {
     Claude,
@@ -37,32 +39,213 @@ pub struct RawEvent {
     pub session_id: Option<String>,
     pub cwd: Option<String>,
     pub uuid: Option<String>,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<String>,
+    /// On `summary` events: the last message of the conversation being summarized
+    #[serde(rename = "leafUuid")]
+    pub leaf_uuid: Option<String>,
+    pub summary: Option<String>,
     pub timestamp: Option<String>,
     pub message: Option<RawMessage>,
     #[serde(rename = "durationMs")]
     pub duration_ms: Option<f64>,
+    /// Set on events from a subagent (Task tool) conversation
+    #[serde(rename = "isSidechain", default)]
+    pub is_sidechain: bool,
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RawMessage {
     pub role: String,
-    pub content: serde_json::Value, // string | ContentBlock[]
+    #[serde(default)]
+    pub content: MessageContent,
     pub model: Option<String>,
     pub id: Option<String>,
     pub usage: Option<TokenUsage>,
 }
 
+/// A message's `content`: a plain string or an array of content blocks
+#[derive(Debug)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+/// One content block. Only the fields aggregation uses are kept; the rest are skipped
+/// while parsing, and tool result bodies are cut to a short preview as they are read.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContentBlock {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: Option<String>,
+    pub thinking: Option<String>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub input: Option<serde_json::Value>,
+    pub tool_use_id: Option<String>,
+    pub is_error: Option<bool>,
+    pub content: Option<ResultPreview>,
+}
+
+/// Longest tool output kept per result
+const MAX_RESULT_PREVIEW_BYTES: usize = 2000;
+
+/// Start of a tool result's text: a string, or the text blocks of an array joined by
+/// newlines. Images and other blocks are skipped.
+#[derive(Debug, Default)]
+pub struct ResultPreview(pub String);
+
+impl ResultPreview {
+    fn push(&mut self, text: &str) {
+        let room = MAX_RESULT_PREVIEW_BYTES.saturating_sub(self.0.len());
+        if room == 0 || text.is_empty() {
+            return;
+        }
+        if !self.0.is_empty() {
+            self.0.push('\n');
+        }
+        self.0.push_str(&text[..text.floor_char_boundary(room)]);
+    }
+}
+
+impl<'de> Deserialize<'de> for ResultPreview {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct ResultBlock {
+            text: Option<ResultPreview>,
+        }
+
+        struct PreviewVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for PreviewVisitor {
+            type Value = ResultPreview;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a string or an array of content blocks")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                let mut preview = ResultPreview::default();
+                preview.push(s);
+                Ok(preview)
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                Ok(ResultPreview::default())
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut preview = ResultPreview::default();
+                while let Some(block) = seq.next_element::<ResultBlock>()? {
+                    if let Some(text) = block.text {
+                        preview.push(&text.0);
+                    }
+                }
+                Ok(preview)
+            }
+        }
+
+        deserializer.deserialize_any(PreviewVisitor)
+    }
+}
+
+// Hand-written rather than `#[serde(untagged)]`, which would buffer the whole value first
+impl<'de> Deserialize<'de> for MessageContent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ContentVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ContentVisitor {
+            type Value = MessageContent;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a string or an array of content blocks")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(MessageContent::Text(s.to_string()))
+            }
+
+            fn visit_string<E: serde::de::Error>(self, s: String) -> Result<Self::Value, E> {
+                Ok(MessageContent::Text(s))
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                Ok(MessageContent::default())
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut blocks = Vec::new();
+                while let Some(block) = seq.next_element()? {
+                    blocks.push(block);
+                }
+                Ok(MessageContent::Blocks(blocks))
+            }
+        }
+
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+impl MessageContent {
+    /// Blocks of the given type; a plain string has none
+    pub fn blocks<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a ContentBlock> + 'a {
+        let blocks: &[ContentBlock] = match self {
+            MessageContent::Blocks(blocks) => blocks,
+            MessageContent::Text(_) => &[],
+        };
+        blocks.iter().filter(move |b| b.kind == kind)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TokenUsage {
     pub input_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
     pub cache_read_input_tokens: Option<u64>,
     pub cache_creation_input_tokens: Option<u64>,
+    /// Cache writes split by TTL; absent in older logs
+    pub cache_creation: Option<CacheCreation>,
+}
+
+impl TokenUsage {
+    pub fn has_tokens(&self) -> bool {
+        [
+            self.input_tokens,
+            self.output_tokens,
+            self.cache_read_input_tokens,
+            self.cache_creation_input_tokens,
+        ]
+        .iter()
+        .any(|t| t.unwrap_or(0) > 0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheCreation {
+    pub ephemeral_5m_input_tokens: Option<u64>,
+    pub ephemeral_1h_input_tokens: Option<u64>,
 }
 
 // --- Processed types ---
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TokenTotals {
     pub input: u64,
     pub output: u64,
@@ -78,34 +261,204 @@ impl TokenTotals {
     pub fn total(&self) -> u64 {
         self.input + self.output
     }
+
+    pub fn add(&mut self, other: &TokenTotals) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_read += other.cache_read;
+        self.cache_creation += other.cache_creation;
+    }
+
+    pub fn add_usage(&mut self, usage: &TokenUsage) {
+        self.input += usage.input_tokens.unwrap_or(0);
+        self.output += usage.output_tokens.unwrap_or(0);
+        self.cache_read += usage.cache_read_input_tokens.unwrap_or(0);
+        self.cache_creation += usage.cache_creation_input_tokens.unwrap_or(0);
+    }
+
+    pub fn subtract(&mut self, other: &TokenTotals) {
+        self.input = self.input.saturating_sub(other.input);
+        self.output = self.output.saturating_sub(other.output);
+        self.cache_read = self.cache_read.saturating_sub(other.cache_read);
+        self.cache_creation = self.cache_creation.saturating_sub(other.cache_creation);
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Cost split by token kind, in dollars
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CostParts {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl CostParts {
+    pub fn total(&self) -> f64 {
+        self.input + self.output + self.cache_write + self.cache_read
+    }
+
+    pub fn add(&mut self, other: &CostParts) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_write += other.cache_write;
+        self.cache_read += other.cache_read;
+    }
+
+    pub fn subtract(&mut self, other: &CostParts) {
+        self.input = (self.input - other.input).max(0.0);
+        self.output = (self.output - other.output).max(0.0);
+        self.cache_write = (self.cache_write - other.cache_write).max(0.0);
+        self.cache_read = (self.cache_read - other.cache_read).max(0.0);
+    }
+}
+
+/// Usage left out of the totals because an earlier session file already counted it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DedupStats {
+    pub messages: u64,
+    pub tokens: TokenTotals,
+    pub cost: f64,
+}
+
+impl DedupStats {
+    pub fn add(&mut self, other: &DedupStats) {
+        self.messages += other.messages;
+        self.tokens.add(&other.tokens);
+        self.cost += other.cost;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages == 0
+    }
+}
+
+/// Results of a tool's calls, for calls whose `tool_result` was seen
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ToolOutcomes {
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+impl ToolOutcomes {
+    pub fn add(&mut self, other: &ToolOutcomes) {
+        self.succeeded += other.succeeded;
+        self.failed += other.failed;
+    }
+
+    pub fn subtract(&mut self, other: &ToolOutcomes) {
+        self.succeeded = self.succeeded.saturating_sub(other.succeeded);
+        self.failed = self.failed.saturating_sub(other.failed);
+    }
+
+    pub fn record(&mut self, is_error: bool) {
+        if is_error {
+            self.failed += 1;
+        } else {
+            self.succeeded += 1;
+        }
+    }
+
+    /// Share of results that were errors, if any results were seen
+    pub fn failure_rate(&self) -> Option<f64> {
+        let total = self.succeeded + self.failed;
+        (total > 0).then(|| self.failed as f64 / total as f64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileContribution {
     pub added: u64,
     pub removed: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationMessage {
     pub role: String,
     pub timestamp: String,
     pub uuid: String,
     pub usage: Option<TokenUsage>,
     pub content: String,
+    /// Cost of this message's usage, priced with its own model
+    pub cost: f64,
+    /// Model that produced an assistant message
+    pub model: Option<String>,
+    /// API message id and request id of an assistant message, shared by its copies in other files
+    pub message_id: Option<String>,
+    pub request_id: Option<String>,
+    /// Copy of a message an earlier session file already counted; its usage is left out of totals
+    pub duplicate: bool,
+    /// Extended-thinking text, kept apart from `content` (capped like it)
+    pub thinking: String,
+    /// Full length of the thinking text, in characters
+    pub thinking_chars: u64,
+    /// Thinking blocks in the message, including redacted ones whose text isn't available
+    pub thinking_blocks: u32,
+    /// Tool calls an assistant message made
+    pub tool_uses: Vec<ToolUse>,
+    /// Lines its tool calls added and removed
+    pub edits: Vec<EditContribution>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// A tool call as kept for display and search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolUse {
+    pub id: String,
+    pub name: String,
+    /// Program and subcommand of a shell command
+    pub command: Option<String>,
+    /// The argument that says what the call is about: its file path, command or pattern
+    pub summary: String,
+    /// String values of the input, one per line (capped)
+    pub input: String,
+    /// What the call returned, once its result is found
+    pub result: Option<ToolOutput>,
+}
+
+/// Output of a tool call, from the `tool_result` block answering it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolOutput {
+    pub is_error: bool,
+    /// Start of the output text (capped)
+    pub output: String,
+}
+
+impl ConversationMessage {
+    /// Identity used to spot the same API response copied into several session files
+    pub fn dedup_key(&self) -> Option<String> {
+        let id = self.message_id.as_deref()?;
+        Some(format!("{}:{}", id, self.request_id.as_deref().unwrap_or("")))
+    }
+}
+
+/// A commit in a project's repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitCommit {
+    pub hash: String,
+    /// Committer date, RFC 3339
+    pub timestamp: String,
+    pub subject: String,
+    /// Absolute paths of the files it changed
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedSession {
     pub session_id: String,
     pub project_id: String,
     pub cwd: String,
     pub messages: Vec<ConversationMessage>,
     pub tool_usage: HashMap<String, u64>,
+    pub tool_outcomes: HashMap<String, ToolOutcomes>,
+    /// Shell commands run through the Bash tool, keyed by program and subcommand
+    pub command_usage: HashMap<String, u64>,
+    pub command_outcomes: HashMap<String, ToolOutcomes>,
     pub total_tokens: TokenTotals,
     pub duration_ms: f64,
     pub lines_added: u64,
     pub lines_removed: u64,
+    /// Edits whose line counts are a lower bound (see `EditContribution::partial`)
+    pub partial_edits: u64,
     pub file_contributions: HashMap<String, FileContribution>,
     pub first_prompt: String,
     pub started_at: String,
@@ -115,6 +468,95 @@ pub struct ParsedSession {
     pub human_chars: u64,
     pub model: String,
     pub source: DataSource,
+    pub cost: f64,
+    pub cost_parts: CostParts,
+    /// Cost per model, keyed by the full model name
+    pub cost_by_model: HashMap<String, f64>,
+    /// Models that used tokens but have no price
+    pub unpriced_models: Vec<String>,
+    /// Characters of extended thinking, and assistant turns that thought
+    pub thinking_chars: u64,
+    pub thinking_turns: u64,
+    /// Session a subagent transcript ran under
+    pub parent_session: Option<String>,
+    /// Subagent threads; their usage is included in this session's totals
+    pub agents: Vec<ParsedSession>,
+    /// Repeated usage removed from this session's totals
+    pub deduped: DedupStats,
+    /// Title from the session's `summary` event, if any
+    pub summary: String,
+    /// Message uuids in other session files that this session resumes or continues from
+    pub continues_from: Vec<String>,
+    /// Session this one continues, once linked within its project
+    pub previous_session: Option<String>,
+    /// First session of the conversation thread this one belongs to
+    pub thread_id: String,
+    /// Commits in the project's repository attributed to this session
+    pub commits: Vec<GitCommit>,
+    /// File the session was read from: its JSONL transcript, or Cursor's workspace database
+    pub source_path: String,
+}
+
+impl ParsedSession {
+    /// Fold a subagent thread's usage into this session and keep it for drill-down.
+    /// Human input counts and duration are left alone: the agent's prompts come from the
+    /// parent model and it runs inside one of the parent's turns.
+    pub fn attach_agent(&mut self, mut agent: ParsedSession) {
+        self.total_tokens.add(&agent.total_tokens);
+        self.deduped.add(&agent.deduped);
+        self.lines_added += agent.lines_added;
+        self.lines_removed += agent.lines_removed;
+        self.partial_edits += agent.partial_edits;
+        self.thinking_chars += agent.thinking_chars;
+        self.thinking_turns += agent.thinking_turns;
+        self.cost_parts.add(&agent.cost_parts);
+        self.cost = self.cost_parts.total();
+
+        for (model, cost) in &agent.cost_by_model {
+            *self.cost_by_model.entry(model.clone()).or_insert(0.0) += cost;
+        }
+        for (tool, count) in &agent.tool_usage {
+            *self.tool_usage.entry(tool.clone()).or_insert(0) += count;
+        }
+        for (tool, outcome) in &agent.tool_outcomes {
+            self.tool_outcomes.entry(tool.clone()).or_default().add(outcome);
+        }
+        for (command, count) in &agent.command_usage {
+            *self.command_usage.entry(command.clone()).or_insert(0) += count;
+        }
+        for (command, outcome) in &agent.command_outcomes {
+            self.command_outcomes.entry(command.clone()).or_default().add(outcome);
+        }
+        for (path, fc) in &agent.file_contributions {
+            let entry = self
+                .file_contributions
+                .entry(path.clone())
+                .or_insert(FileContribution { added: 0, removed: 0 });
+            entry.added += fc.added;
+            entry.removed += fc.removed;
+        }
+        for model in &agent.unpriced_models {
+            if !self.unpriced_models.contains(model) {
+                self.unpriced_models.push(model.clone());
+            }
+        }
+        if agent.last_active > self.last_active {
+            self.last_active = agent.last_active.clone();
+        }
+
+        agent.parent_session = Some(self.session_id.clone());
+        self.agents.push(agent);
+    }
+
+    /// Cost of the subagent threads alone
+    pub fn agents_cost(&self) -> f64 {
+        self.agents.iter().map(|a| a.cost).sum()
+    }
+
+    /// Spend per commit produced, if the session produced any
+    pub fn cost_per_commit(&self) -> Option<f64> {
+        (!self.commits.is_empty()).then(|| self.cost / self.commits.len() as f64)
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -122,7 +564,10 @@ pub struct SessionFile {
     pub id: String,
     pub path: String,
     pub size: u64,
+    pub modified: Option<std::time::SystemTime>,
     pub source: DataSource,
+    /// Session a subagent transcript belongs to
+    pub parent_session: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -134,6 +579,25 @@ pub struct ScannedProject {
     pub session_files: Vec<SessionFile>,
 }
 
+/// Sessions linked by resuming or continuing into one logical conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationThread {
+    /// Id of the first session
+    pub id: String,
+    /// Sessions in the order they were started
+    pub session_ids: Vec<String>,
+    /// Most recent summary title in the thread
+    pub summary: String,
+    pub message_count: usize,
+    pub total_tokens: TokenTotals,
+    pub cost: f64,
+    pub lines_added: u64,
+    pub lines_removed: u64,
+    pub duration_ms: f64,
+    pub started_at: String,
+    pub last_active: String,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ProjectSummary {
     pub id: String,
@@ -146,10 +610,36 @@ pub struct ProjectSummary {
     pub lines_removed: u64,
     pub last_active: String,
     pub tool_usage: HashMap<String, u64>,
+    pub tool_outcomes: HashMap<String, ToolOutcomes>,
+    pub command_usage: HashMap<String, u64>,
+    pub command_outcomes: HashMap<String, ToolOutcomes>,
     pub cost: f64,
+    pub cost_parts: CostParts,
+    pub cost_by_model: HashMap<String, f64>,
+    pub deduped: DedupStats,
+    pub thinking_chars: u64,
+    pub thinking_turns: u64,
     pub model: String,
     pub sessions: Vec<ParsedSession>,
     pub sources: Vec<DataSource>,
+    /// Threads of more than one session
+    pub threads: Vec<ConversationThread>,
+    /// Commits attributed to any of the sessions
+    pub commit_count: usize,
+}
+
+impl ProjectSummary {
+    /// Total spend per commit produced, sessions without commits included
+    pub fn cost_per_commit(&self) -> Option<f64> {
+        (self.commit_count > 0).then(|| self.cost / self.commit_count as f64)
+    }
+
+    /// The multi-session thread a session belongs to, with its 0-based position in it
+    pub fn thread_of(&self, session: &ParsedSession) -> Option<(usize, &ConversationThread)> {
+        let thread = self.threads.iter().find(|t| t.id == session.thread_id)?;
+        let pos = thread.session_ids.iter().position(|id| *id == session.session_id)?;
+        Some((pos, thread))
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -170,10 +660,19 @@ pub struct GlobalMetrics {
     pub total_messages: usize,
     pub total_tokens: TokenTotals,
     pub tool_usage: HashMap<String, u64>,
+    pub tool_outcomes: HashMap<String, ToolOutcomes>,
+    pub command_usage: HashMap<String, u64>,
+    pub command_outcomes: HashMap<String, ToolOutcomes>,
     pub timeline: Vec<TimelineEntry>,
     pub total_lines_added: u64,
     pub total_lines_removed: u64,
     pub total_cost: f64,
+    pub cost_parts: CostParts,
+    pub cost_by_model: HashMap<String, f64>,
+    pub unpriced_models: Vec<String>,
+    pub deduped: DedupStats,
+    pub thinking_chars: u64,
+    pub thinking_turns: u64,
     pub human_lines: u64,
     pub human_words: u64,
     pub human_chars: u64,
@@ -187,10 +686,19 @@ impl GlobalMetrics {
             total_messages: 0,
             total_tokens: TokenTotals::zero(),
             tool_usage: HashMap::new(),
+            tool_outcomes: HashMap::new(),
+            command_usage: HashMap::new(),
+            command_outcomes: HashMap::new(),
             timeline: Vec::new(),
             total_lines_added: 0,
             total_lines_removed: 0,
             total_cost: 0.0,
+            cost_parts: CostParts::default(),
+            cost_by_model: HashMap::new(),
+            unpriced_models: Vec::new(),
+            deduped: DedupStats::default(),
+            thinking_chars: 0,
+            thinking_turns: 0,
             human_lines: 0,
             human_words: 0,
             human_chars: 0,
@@ -1,314 +1,768 @@
-use anyhow::Result;
-use std::collections::HashMap;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 
+use crate::edits;
 use crate::models::{
-    ConversationMessage, DataSource, FileContribution, ParsedSession, RawEvent, TokenTotals,
+    ConversationMessage, CostParts, DataSource, DedupStats, FileContribution, ParsedSession,
+    MessageContent, RawEvent, RawMessage, TokenTotals, ToolOutcomes, ToolOutput, ToolUse,
 };
+use crate::pricing::{usage_cost, UNKNOWN_MODEL};
+use crate::shell::{command_key, SHELL_TOOL};
+use crate::window::TimeWindow;
 
 const SKIP_TYPES: &[&str] = &["progress", "queue-operation", "file-history-snapshot"];
 
-struct TaggedEvent {
-    kind: &'static str, // "user" or "assistant"
-    event: RawEvent,
-    ts: String,
+/// Bytes remembered before the resume offset, to detect files rewritten in place
+const TAIL_LEN: usize = 32;
+
+/// Longest assistant text, thinking or tool input kept per message; counts are taken
+/// from the full text first. User prompts are kept whole.
+const MAX_PREVIEW_BYTES: usize = 5000;
+
+/// Line buffer capacity kept between lines; a larger buffer left by one huge line is released
+const LINE_BUF_CAPACITY: usize = 64 * 1024;
+
+/// Input fields naming what a tool call works on, most telling first
+const KEY_ARGUMENTS: &[&str] = &[
+    "file_path", "notebook_path", "path", "command", "pattern", "url", "query", "description", "prompt",
+];
+
+/// Resumable parse state for one JSONL session file.
+///
+/// Claude Code only ever appends to session files, so `update` streams from the
+/// last consumed byte and folds in new lines one at a time; events are never
+/// buffered. Memory per file is one line (the buffer shrinks back to
+/// `LINE_BUF_CAPACITY` after an oversized one) plus what is kept per message:
+/// user prompts in full, assistant text and thinking up to `MAX_PREVIEW_BYTES`
+/// each, and per tool call its key argument and input up to `MAX_PREVIEW_BYTES`
+/// and its result up to 2000 bytes. It grows with the number of messages, not
+/// with the size of responses, tool inputs or tool outputs.
+/// Assistant events are kept per `message.id`: Claude Code writes one line per
+/// content block of a response, so later lines are merged into the first and
+/// totals are derived from the entries in `to_session`.
+///
+/// Subagent (sidechain) events interleaved in a main transcript are folded into
+/// a nested state per agent and attached to the session as agent threads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionState {
+    offset: u64,
+    tail: Vec<u8>,
+    cwd: String,
+    started_at: String,
+    last_active: String,
+    duration_ms: f64,
+    users: Vec<UserEntry>,
+    assistants: Vec<AssistantEntry>,
+    assistant_index: HashMap<String, usize>,
+    /// Whether this transcript is itself a subagent's, decided by its first event
+    is_agent: Option<bool>,
+    /// Sidechain conversations found in a main transcript, by agent session id
+    agents: BTreeMap<String, SessionState>,
+    /// Uuids this transcript links back to: `summary` leaves and the first message's parent
+    links: Vec<String>,
+    summary: String,
+    seen_message: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UserEntry {
+    message: ConversationMessage,
+    tool_results: Vec<ToolResult>,
+    prompt: String,
+    human_lines: u64,
+    human_words: u64,
+    human_chars: u64,
 }
 
-pub fn parse_session_file(
-    file_path: &str,
-    session_id: &str,
-    project_id: &str,
-) -> Result<ParsedSession> {
-    let raw = fs::read_to_string(file_path)?;
-
-    let mut user_events: Vec<(RawEvent, String)> = Vec::new();
-    let mut assistant_by_id: HashMap<String, (RawEvent, String)> = HashMap::new();
-    let mut assistant_no_id: Vec<(RawEvent, String)> = Vec::new();
-    let mut duration_ms: f64 = 0.0;
-    let mut cwd = String::new();
-    let mut started_at = String::new();
-    let mut last_active = String::new();
-
-    for line in raw.lines() {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AssistantEntry {
+    message: ConversationMessage,
+    model: Option<String>,
+    /// Fingerprints of the full thinking blocks taken in; `message.thinking` is capped
+    thinking_keys: Vec<u64>,
+    /// Uuids of the lines taken in
+    line_uuids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ToolResult {
+    tool_use_id: String,
+    is_error: bool,
+    /// Start of the output (capped while parsing)
+    output: String,
+}
+
+impl AssistantEntry {
+    /// Merge a later line of the same API response. Each line carries the next content
+    /// block and the latest usage; a line seen again, e.g. in a rewritten file, is skipped.
+    /// Text, thinking, tool calls and edits accumulate across lines rather than the
+    /// last line standing for the whole response, so every block's calls are counted.
+    fn absorb(&mut self, chunk: AssistantEntry) {
+        let uuid = &chunk.message.uuid;
+        if !uuid.is_empty() && self.line_uuids.contains(uuid) {
+            return;
+        }
+        self.line_uuids.extend(chunk.line_uuids);
+
+        if chunk.message.usage.is_some() {
+            self.message.usage = chunk.message.usage;
+        }
+        if self.model.is_none() {
+            self.model = chunk.model;
+        }
+        self.message.content = merge_text(&self.message.content, &chunk.message.content);
+        let repeated = !chunk.thinking_keys.is_empty()
+            && chunk.thinking_keys.iter().all(|k| self.thinking_keys.contains(k));
+        if !repeated {
+            self.message.thinking = merge_text(&self.message.thinking, &chunk.message.thinking);
+            self.message.thinking_chars += chunk.message.thinking_chars;
+            self.message.thinking_blocks += chunk.message.thinking_blocks;
+            self.thinking_keys.extend(chunk.thinking_keys);
+        }
+
+        // Edits can't be matched to calls, so take them only from lines that bring new calls
+        let new_tools: Vec<ToolUse> = chunk
+            .message
+            .tool_uses
+            .into_iter()
+            .filter(|t| t.id.is_empty() || !self.message.tool_uses.iter().any(|k| k.id == t.id))
+            .collect();
+        if !new_tools.is_empty() {
+            self.message.tool_uses.extend(new_tools);
+            self.message.edits.extend(chunk.message.edits);
+        }
+    }
+}
+
+/// Join the text of two lines of one response, tolerating lines that repeat earlier text
+fn merge_text(earlier: &str, later: &str) -> String {
+    if later.is_empty() || earlier.contains(later) {
+        earlier.to_string()
+    } else if earlier.is_empty() || later.starts_with(earlier) {
+        later.to_string()
+    } else {
+        preview(&format!("{}\n{}", earlier, later))
+    }
+}
+
+impl SessionState {
+    /// Bytes of the file consumed so far
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Fold in lines appended since the last call. Starts over if the file was
+    /// truncated or rewritten. Returns whether any new bytes were consumed.
+    pub fn update(&mut self, file_path: &str, window: &TimeWindow) -> Result<bool> {
+        let mut file = fs::File::open(file_path)?;
+        let len = file.metadata()?.len();
+
+        if len < self.offset || !self.tail_matches(&mut file)? {
+            *self = SessionState::default();
+        }
+        if len == self.offset {
+            return Ok(false);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut buf: Vec<u8> = Vec::with_capacity(LINE_BUF_CAPACITY);
+        let start = self.offset;
+
+        loop {
+            buf.clear();
+            let n = reader.read_until(b'\n', &mut buf)?;
+            if n == 0 {
+                break;
+            }
+
+            let line = String::from_utf8_lossy(&buf);
+            // A final line without newline may still be mid-write; leave it for next time
+            if buf.last() != Some(&b'\n')
+                && serde_json::from_str::<serde_json::Value>(line.trim()).is_err()
+            {
+                break;
+            }
+
+            self.fold_line(&line, window);
+            self.offset += n as u64;
+            let keep = buf.len().min(TAIL_LEN);
+            self.tail = buf[buf.len() - keep..].to_vec();
+
+            // Don't hold on to the memory of one oversized line for the rest of the file
+            if buf.capacity() > LINE_BUF_CAPACITY {
+                buf = Vec::with_capacity(LINE_BUF_CAPACITY);
+            }
+        }
+
+        Ok(self.offset > start)
+    }
+
+    fn tail_matches(&self, file: &mut fs::File) -> Result<bool> {
+        if self.offset == 0 {
+            return Ok(true);
+        }
+        let mut actual = vec![0u8; self.tail.len()];
+        file.seek(SeekFrom::Start(self.offset - self.tail.len() as u64))?;
+        if file.read_exact(&mut actual).is_err() {
+            return Ok(false);
+        }
+        Ok(actual == self.tail)
+    }
+
+    fn fold_line(&mut self, line: &str, window: &TimeWindow) {
         let line = line.trim();
         if line.is_empty() {
-            continue;
+            return;
         }
 
         let event: RawEvent = match serde_json::from_str(line) {
             Ok(e) => e,
-            Err(_) => continue,
+            Err(_) => return,
         };
 
         if SKIP_TYPES.contains(&event.event_type.as_str()) {
-            continue;
+            return;
+        }
+
+        let is_agent = *self.is_agent.get_or_insert(event.is_sidechain);
+        if event.is_sidechain && !is_agent {
+            let key = match event.agent_id {
+                Some(ref id) => format!("agent-{}", id),
+                None => "sidechain".to_string(),
+            };
+            self.agents.entry(key).or_default().fold_event(event, window);
+            return;
+        }
+        self.fold_event(event, window);
+    }
+
+    fn fold_event(&mut self, event: RawEvent, window: &TimeWindow) {
+        // A resumed or continued conversation points back into the file it came from
+        if event.event_type == "summary" {
+            if let Some(ref leaf) = event.leaf_uuid {
+                if !self.links.contains(leaf) {
+                    self.links.push(leaf.clone());
+                }
+            }
+            if let Some(ref summary) = event.summary {
+                self.summary = summary.clone();
+            }
+            return;
+        }
+        if event.message.is_some() && !self.seen_message {
+            self.seen_message = true;
+            if let Some(ref parent) = event.parent_uuid {
+                self.links.push(parent.clone());
+            }
         }
 
-        if cwd.is_empty() {
+        if self.cwd.is_empty() {
             if let Some(ref c) = event.cwd {
-                cwd = c.clone();
+                self.cwd = c.clone();
             }
         }
 
         let ts = event.timestamp.clone().unwrap_or_default();
+        if !window.contains_ts(&ts) {
+            return;
+        }
         if !ts.is_empty() {
-            if started_at.is_empty() {
-                started_at = ts.clone();
+            if self.started_at.is_empty() {
+                self.started_at = ts.clone();
             }
-            last_active = ts.clone();
+            self.last_active = ts.clone();
         }
 
         if event.event_type == "system" {
             if event.subtype.as_deref() == Some("turn_duration") {
                 if let Some(d) = event.duration_ms {
-                    duration_ms += d;
-                }
-            }
-            continue;
-        }
-
-        if event.event_type == "user" {
-            if let Some(ref msg) = event.message {
-                if msg.role == "user" {
-                    user_events.push((event, ts));
-                    continue;
+                    self.duration_ms += d;
                 }
             }
+            return;
         }
 
-        if event.event_type == "assistant" {
-            if let Some(ref msg) = event.message {
-                if msg.role == "assistant" {
-                    if let Some(ref msg_id) = msg.id {
-                        assistant_by_id.insert(msg_id.clone(), (event, ts));
+        let msg = match event.message {
+            Some(ref m) => m,
+            None => return,
+        };
+        let uuid = event.uuid.clone().unwrap_or_default();
+
+        if event.event_type == "user" && msg.role == "user" {
+            self.users.push(user_entry(msg, uuid, ts));
+        } else if event.event_type == "assistant" && msg.role == "assistant" {
+            let entry = assistant_entry(msg, uuid, ts, event.request_id.clone());
+            match msg.id {
+                Some(ref msg_id) => {
+                    if let Some(&idx) = self.assistant_index.get(msg_id) {
+                        self.assistants[idx].absorb(entry);
                     } else {
-                        assistant_no_id.push((event, ts));
+                        self.assistant_index.insert(msg_id.clone(), self.assistants.len());
+                        self.assistants.push(entry);
                     }
                 }
+                None => self.assistants.push(entry),
             }
         }
     }
 
-    // Build messages list
-    let mut messages: Vec<ConversationMessage> = Vec::new();
-    let mut tool_usage: HashMap<String, u64> = HashMap::new();
-    let mut tokens = TokenTotals::zero();
-    let mut lines_added: u64 = 0;
-    let mut lines_removed: u64 = 0;
-    let mut file_contributions: HashMap<String, FileContribution> = HashMap::new();
-    let mut first_prompt = String::new();
-    let mut human_lines: u64 = 0;
-    let mut human_words: u64 = 0;
-    let mut human_chars: u64 = 0;
-    let mut model = String::new();
-
-    // Collect all deduplicated assistant events
-    let all_assistant: Vec<(RawEvent, String)> = assistant_by_id
-        .into_values()
-        .chain(assistant_no_id)
-        .collect();
-
-    // Merge and sort by timestamp
-    let mut all_events: Vec<TaggedEvent> = Vec::new();
-    for (event, ts) in user_events {
-        all_events.push(TaggedEvent { kind: "user", event, ts });
-    }
-    for (event, ts) in all_assistant {
-        all_events.push(TaggedEvent { kind: "assistant", event, ts });
-    }
-    all_events.sort_by(|a, b| a.ts.cmp(&b.ts));
-
-    for tagged in &all_events {
-        let msg = match &tagged.event.message {
-            Some(m) => m,
-            None => continue,
-        };
-
-        if tagged.kind == "user" {
-            let user_text = extract_text(&msg.content);
-            if first_prompt.is_empty() {
-                first_prompt = user_text;
-            }
-
-            // Count human contribution
-            let raw_text = extract_raw_text(&msg.content);
-            let stripped = strip_html(&raw_text);
-            let stripped = stripped.trim();
-            if !stripped.is_empty() {
-                human_lines += stripped.lines().count() as u64;
-                human_words += stripped.split_whitespace().count() as u64;
-                human_chars += stripped.len() as u64;
-            }
-
-            // Extract content for message thread
-            let content = extract_raw_text(&msg.content);
-            let content = strip_html(&content);
-
-            messages.push(ConversationMessage {
-                role: "user".to_string(),
-                timestamp: tagged.ts.clone(),
-                uuid: tagged.event.uuid.clone().unwrap_or_default(),
-                usage: None,
-                content: content.trim().to_string(),
-            });
-        } else {
-            // Extract assistant text content (truncate to manage memory)
-            let raw_content = extract_raw_text(&msg.content);
-            let mut content = strip_html(&raw_content);
-            if content.len() > 5000 {
-                content.truncate(5000);
-                content.push_str("...");
-            }
-
-            messages.push(ConversationMessage {
-                role: "assistant".to_string(),
-                timestamp: tagged.ts.clone(),
-                uuid: tagged.event.uuid.clone().unwrap_or_default(),
-                usage: msg.usage.clone(),
-                content: content.trim().to_string(),
-            });
+    /// Build the aggregated session from the current state
+    pub fn to_session(&self, session_id: &str, project_id: &str) -> ParsedSession {
+        enum Entry<'a> {
+            User(&'a UserEntry),
+            Assistant(&'a AssistantEntry),
+        }
 
-            if model.is_empty() {
-                if let Some(ref m) = msg.model {
-                    model = m.clone();
+        // Merge and sort by timestamp
+        let mut all_events: Vec<(&str, Entry)> = Vec::new();
+        for u in &self.users {
+            all_events.push((&u.message.timestamp, Entry::User(u)));
+        }
+        for a in &self.assistants {
+            all_events.push((&a.message.timestamp, Entry::Assistant(a)));
+        }
+        all_events.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut messages: Vec<ConversationMessage> = Vec::with_capacity(all_events.len());
+        let mut tool_usage: HashMap<String, u64> = HashMap::new();
+        let mut tokens = TokenTotals::zero();
+        let mut lines_added: u64 = 0;
+        let mut lines_removed: u64 = 0;
+        let mut partial_edits: u64 = 0;
+        let mut file_contributions: HashMap<String, FileContribution> = HashMap::new();
+        let mut first_prompt = String::new();
+        let mut human_lines: u64 = 0;
+        let mut human_words: u64 = 0;
+        let mut human_chars: u64 = 0;
+        let mut model = String::new();
+        let mut cost_parts = CostParts::default();
+        let mut cost_by_model: HashMap<String, f64> = HashMap::new();
+        let mut unpriced_models: Vec<String> = Vec::new();
+        let mut tool_outcomes: HashMap<String, ToolOutcomes> = HashMap::new();
+        let mut command_usage: HashMap<String, u64> = HashMap::new();
+        let mut command_outcomes: HashMap<String, ToolOutcomes> = HashMap::new();
+        let mut thinking_chars: u64 = 0;
+        let mut thinking_turns: u64 = 0;
+
+        // Results arrive in later user events, so index every call by id first
+        let calls: HashMap<&str, &ToolUse> = self
+            .assistants
+            .iter()
+            .flat_map(|a| &a.message.tool_uses)
+            .filter(|t| !t.id.is_empty())
+            .map(|t| (t.id.as_str(), t))
+            .collect();
+        let results: HashMap<&str, &ToolResult> = self
+            .users
+            .iter()
+            .flat_map(|u| &u.tool_results)
+            .map(|r| (r.tool_use_id.as_str(), r))
+            .collect();
+
+        for (_, entry) in all_events {
+            match entry {
+                Entry::User(u) => {
+                    if first_prompt.is_empty() {
+                        first_prompt = u.prompt.clone();
+                    }
+                    human_lines += u.human_lines;
+                    human_words += u.human_words;
+                    human_chars += u.human_chars;
+                    for result in &u.tool_results {
+                        let Some(call) = calls.get(result.tool_use_id.as_str()) else {
+                            continue;
+                        };
+                        tool_outcomes.entry(call.name.clone()).or_default().record(result.is_error);
+                        if let Some(ref command) = call.command {
+                            command_outcomes.entry(command.clone()).or_default().record(result.is_error);
+                        }
+                    }
+                    messages.push(u.message.clone());
                 }
-            }
-
-            // Count tokens
-            if let Some(ref usage) = msg.usage {
-                tokens.input += usage.input_tokens.unwrap_or(0);
-                tokens.output += usage.output_tokens.unwrap_or(0);
-                tokens.cache_read += usage.cache_read_input_tokens.unwrap_or(0);
-                tokens.cache_creation += usage.cache_creation_input_tokens.unwrap_or(0);
-            }
-
-            // Count tool uses and code contribution
-            if let serde_json::Value::Array(ref blocks) = msg.content {
-                for block in blocks {
-                    let block_type = block.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                    if block_type != "tool_use" {
-                        continue;
+                Entry::Assistant(a) => {
+                    if model.is_empty() {
+                        if let Some(ref m) = a.model {
+                            model = m.clone();
+                        }
                     }
 
-                    if let Some(name) = block.get("name").and_then(|v| v.as_str()) {
-                        *tool_usage.entry(name.to_string()).or_insert(0) += 1;
-
-                        if name == "Write" {
-                            if let Some(content) = block
-                                .get("input")
-                                .and_then(|i| i.get("content"))
-                                .and_then(|c| c.as_str())
+                    // Count tokens and price them with this message's model
+                    let mut message = a.message.clone();
+                    for tool_use in &mut message.tool_uses {
+                        tool_use.result = results.get(tool_use.id.as_str()).map(|r| ToolOutput {
+                            is_error: r.is_error,
+                            output: r.output.trim_end().to_string(),
+                        });
+                    }
+                    if let Some(ref usage) = a.message.usage {
+                        tokens.add_usage(usage);
+
+                        let msg_model = a.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+                        match usage_cost(msg_model, usage, &a.message.timestamp) {
+                            Some(parts) => {
+                                message.cost = parts.total();
+                                cost_parts.add(&parts);
+                                *cost_by_model.entry(msg_model.to_string()).or_insert(0.0) +=
+                                    message.cost;
+                            }
+                            None if usage.has_tokens()
+                                && !unpriced_models.iter().any(|m| m == msg_model) =>
                             {
-                                let lines = content.lines().count() as u64;
-                                lines_added += lines;
-                                if let Some(fp) = block
-                                    .get("input")
-                                    .and_then(|i| i.get("file_path"))
-                                    .and_then(|f| f.as_str())
-                                {
-                                    let fc = file_contributions
-                                        .entry(fp.to_string())
-                                        .or_insert(FileContribution { added: 0, removed: 0 });
-                                    fc.added += lines;
-                                }
+                                unpriced_models.push(msg_model.to_string());
                             }
+                            None => {}
                         }
+                    }
 
-                        if name == "Edit" {
-                            if let Some(input) = block.get("input") {
-                                let old_str = input
-                                    .get("old_string")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("");
-                                let new_str = input
-                                    .get("new_string")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("");
-                                let old_lines = if old_str.is_empty() {
-                                    0
-                                } else {
-                                    old_str.lines().count() as u64
-                                };
-                                let new_lines = if new_str.is_empty() {
-                                    0
-                                } else {
-                                    new_str.lines().count() as u64
-                                };
-                                lines_removed += old_lines;
-                                lines_added += new_lines;
-                                if let Some(fp) = input.get("file_path").and_then(|f| f.as_str()) {
-                                    let fc = file_contributions
-                                        .entry(fp.to_string())
-                                        .or_insert(FileContribution { added: 0, removed: 0 });
-                                    fc.added += new_lines;
-                                    fc.removed += old_lines;
-                                }
-                            }
+                    if a.message.thinking_blocks > 0 {
+                        thinking_turns += 1;
+                        thinking_chars += a.message.thinking_chars;
+                    }
+
+                    for call in &a.message.tool_uses {
+                        *tool_usage.entry(call.name.clone()).or_insert(0) += 1;
+                        if let Some(ref command) = call.command {
+                            *command_usage.entry(command.clone()).or_insert(0) += 1;
+                        }
+                    }
+
+                    for edit in &a.message.edits {
+                        lines_added += edit.added;
+                        lines_removed += edit.removed;
+                        partial_edits += u64::from(edit.partial);
+                        if let Some(ref fp) = edit.file_path {
+                            let fc = file_contributions
+                                .entry(fp.clone())
+                                .or_insert(FileContribution { added: 0, removed: 0 });
+                            fc.added += edit.added;
+                            fc.removed += edit.removed;
                         }
                     }
+
+                    messages.push(message);
                 }
             }
         }
+
+        let mut session = ParsedSession {
+            session_id: session_id.to_string(),
+            project_id: project_id.to_string(),
+            cwd: self.cwd.clone(),
+            messages,
+            tool_usage,
+            tool_outcomes,
+            command_usage,
+            command_outcomes,
+            total_tokens: tokens,
+            duration_ms: self.duration_ms,
+            lines_added,
+            lines_removed,
+            partial_edits,
+            file_contributions,
+            first_prompt,
+            started_at: self.started_at.clone(),
+            last_active: self.last_active.clone(),
+            human_lines,
+            human_words,
+            human_chars,
+            model,
+            source: DataSource::Claude,
+            cost: cost_parts.total(),
+            cost_parts,
+            cost_by_model,
+            unpriced_models,
+            thinking_chars,
+            thinking_turns,
+            parent_session: None,
+            agents: Vec::new(),
+            deduped: DedupStats::default(),
+            summary: self.summary.clone(),
+            continues_from: self.links.clone(),
+            previous_session: None,
+            thread_id: session_id.to_string(),
+            commits: Vec::new(),
+            source_path: String::new(),
+        };
+
+        for (agent_id, state) in &self.agents {
+            let agent = state.to_session(agent_id, project_id);
+            if !agent.messages.is_empty() {
+                session.attach_agent(agent);
+            }
+        }
+        session
     }
+}
 
-    Ok(ParsedSession {
-        session_id: session_id.to_string(),
-        project_id: project_id.to_string(),
-        cwd,
-        messages,
-        tool_usage,
-        total_tokens: tokens,
-        duration_ms,
-        lines_added,
-        lines_removed,
-        file_contributions,
-        first_prompt,
-        started_at,
-        last_active,
+fn user_entry(msg: &RawMessage, uuid: String, ts: String) -> UserEntry {
+    // Count human contribution
+    let raw_text = extract_raw_text(&msg.content);
+    let stripped = strip_html(&raw_text);
+    let trimmed = stripped.trim();
+    let (human_lines, human_words, human_chars) = if trimmed.is_empty() {
+        (0, 0, 0)
+    } else {
+        (
+            trimmed.lines().count() as u64,
+            trimmed.split_whitespace().count() as u64,
+            trimmed.len() as u64,
+        )
+    };
+
+    UserEntry {
+        message: ConversationMessage {
+            role: "user".to_string(),
+            timestamp: ts,
+            uuid,
+            usage: None,
+            content: trimmed.to_string(),
+            cost: 0.0,
+            model: None,
+            message_id: None,
+            request_id: None,
+            duplicate: false,
+            thinking: String::new(),
+            thinking_chars: 0,
+            thinking_blocks: 0,
+            tool_uses: Vec::new(),
+            edits: Vec::new(),
+        },
+        tool_results: tool_results(&msg.content),
+        prompt: extract_text(&msg.content),
         human_lines,
         human_words,
         human_chars,
-        model,
-        source: DataSource::Claude,
-    })
+    }
 }
 
-/// Extract first text block as a single-line string
-fn extract_text(content: &serde_json::Value) -> String {
-    match content {
-        serde_json::Value::String(s) => {
-            s.split_whitespace().collect::<Vec<_>>().join(" ")
-        }
-        serde_json::Value::Array(blocks) => {
-            for block in blocks {
-                if block.get("type").and_then(|v| v.as_str()) == Some("text") {
-                    if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
-                        return text.split_whitespace().collect::<Vec<_>>().join(" ");
-                    }
+/// `tool_result` blocks of a user message, with whether each reported an error
+fn tool_results(content: &MessageContent) -> Vec<ToolResult> {
+    content
+        .blocks("tool_result")
+        .filter_map(|b| {
+            Some(ToolResult {
+                tool_use_id: b.tool_use_id.clone()?,
+                is_error: b.is_error.unwrap_or(false),
+                output: b.content.as_ref().map(|c| c.0.clone()).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn assistant_entry(
+    msg: &RawMessage,
+    uuid: String,
+    ts: String,
+    request_id: Option<String>,
+) -> AssistantEntry {
+    // Kept as written; the thread renders it as Markdown
+    let content = extract_raw_text(&msg.content);
+    let thinking_texts: Vec<&str> = msg
+        .content
+        .blocks("thinking")
+        .filter_map(|b| b.thinking.as_deref())
+        .collect();
+    let thinking_keys = thinking_texts
+        .iter()
+        .map(|text| {
+            let mut hasher = DefaultHasher::new();
+            text.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect();
+    let thinking = thinking_texts.join("\n");
+    let thinking_blocks = msg.content.blocks("thinking").count()
+        + msg.content.blocks("redacted_thinking").count();
+
+    // Count tool uses and code contribution
+    let mut tool_uses = Vec::new();
+    let mut edits = Vec::new();
+    for block in msg.content.blocks("tool_use") {
+        if let Some(ref name) = block.name {
+            let command = match block.input {
+                Some(ref input) if name == SHELL_TOOL => {
+                    input.get("command").and_then(|c| c.as_str()).and_then(command_key)
                 }
+                _ => None,
+            };
+            tool_uses.push(ToolUse {
+                id: block.id.clone().unwrap_or_default(),
+                name: name.clone(),
+                command,
+                summary: block.input.as_ref().map(key_argument).unwrap_or_default(),
+                input: block.input.as_ref().map(input_text).unwrap_or_default(),
+                result: None,
+            });
+
+            if let Some(ref input) = block.input {
+                edits.extend(edits::extract(name, input));
             }
-            String::new()
         }
-        _ => String::new(),
+    }
+
+    AssistantEntry {
+        message: ConversationMessage {
+            role: "assistant".to_string(),
+            timestamp: ts,
+            uuid: uuid.clone(),
+            usage: msg.usage.clone(),
+            content: preview(content.trim()),
+            cost: 0.0,
+            model: msg.model.clone(),
+            message_id: msg.id.clone(),
+            request_id,
+            duplicate: false,
+            thinking_chars: thinking.chars().count() as u64,
+            thinking: preview(thinking.trim()),
+            thinking_blocks: thinking_blocks as u32,
+            tool_uses,
+            edits,
+        },
+        model: msg.model.clone(),
+        thinking_keys,
+        line_uuids: vec![uuid],
     }
 }
 
-/// Extract raw text preserving newlines — for human contribution counting
-pub fn extract_raw_text(content: &serde_json::Value) -> String {
-    match content {
-        serde_json::Value::String(s) => s.clone(),
-        serde_json::Value::Array(blocks) => {
-            let mut texts = Vec::new();
-            for block in blocks {
-                if block.get("type").and_then(|v| v.as_str()) == Some("text") {
-                    if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
-                        texts.push(text.to_string());
-                    }
+/// First line of the input field that best says what a tool call is about
+fn key_argument(input: &Value) -> String {
+    KEY_ARGUMENTS
+        .iter()
+        .find_map(|key| input.get(key)?.as_str())
+        .and_then(|value| value.lines().find(|l| !l.trim().is_empty()))
+        .map(|line| preview(line.trim()))
+        .unwrap_or_default()
+}
+
+/// The string values of a tool input, one per line, capped like message text
+fn input_text(input: &Value) -> String {
+    preview(&input_strings(input))
+}
+
+/// The string values of a tool input, one per line
+fn input_strings(input: &Value) -> String {
+    fn collect<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+        match value {
+            Value::String(s) => out.push(s),
+            Value::Array(items) => items.iter().for_each(|v| collect(v, out)),
+            Value::Object(fields) => fields.values().for_each(|v| collect(v, out)),
+            _ => {}
+        }
+    }
+    let mut parts = Vec::new();
+    collect(input, &mut parts);
+    parts.join("\n")
+}
+
+/// Read a message back from its session file with nothing capped. An assistant message
+/// is gathered from every line of its API response, any other message from its own line.
+pub fn read_full_message(file_path: &str, message: &ConversationMessage) -> Result<ConversationMessage> {
+    let key = message.message_id.as_deref().unwrap_or(&message.uuid);
+    if key.is_empty() {
+        bail!("message has no id to look up");
+    }
+
+    let reader = BufReader::new(fs::File::open(file_path)?);
+    let mut found = false;
+    let mut content: Vec<String> = Vec::new();
+    let mut thinking: Vec<String> = Vec::new();
+    let mut tool_uses: Vec<ToolUse> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        // Cheap check first; only lines mentioning the id are worth parsing
+        if !line.contains(key) {
+            continue;
+        }
+        let Ok(event) = serde_json::from_str::<RawEvent>(&line) else {
+            continue;
+        };
+        let Some(msg) = event.message else {
+            continue;
+        };
+        let matches = match message.message_id {
+            Some(ref id) => msg.id.as_ref() == Some(id),
+            None => event.uuid.as_deref() == Some(message.uuid.as_str()),
+        };
+        if !matches {
+            continue;
+        }
+        found = true;
+
+        let text = extract_raw_text(&msg.content).trim().to_string();
+        if !text.is_empty() && !content.contains(&text) {
+            content.push(text);
+        }
+        for block in msg.content.blocks("thinking") {
+            if let Some(text) = block.thinking.as_deref().map(str::trim) {
+                if !text.is_empty() && !thinking.iter().any(|t| t == text) {
+                    thinking.push(text.to_string());
                 }
             }
-            texts.join("\n")
         }
-        _ => String::new(),
+        for block in msg.content.blocks("tool_use") {
+            let id = block.id.clone().unwrap_or_default();
+            if id.is_empty() || !tool_uses.iter().any(|t| t.id == id) {
+                // Results come from other lines; keep the ones already paired
+                let stored = message.tool_uses.iter().find(|t| !id.is_empty() && t.id == id);
+                tool_uses.push(ToolUse {
+                    id,
+                    name: block.name.clone().unwrap_or_default(),
+                    command: stored.and_then(|t| t.command.clone()),
+                    summary: block.input.as_ref().map(key_argument).unwrap_or_default(),
+                    input: block.input.as_ref().map(input_strings).unwrap_or_default(),
+                    result: stored.and_then(|t| t.result.clone()),
+                });
+            }
+        }
+    }
+    if !found {
+        bail!("message {} not found in {}", key, file_path);
+    }
+
+    let mut full = message.clone();
+    full.content = content.join("\n");
+    full.thinking = thinking.join("\n");
+    full.tool_uses = tool_uses;
+    Ok(full)
+}
+
+/// Message text kept for display, capped so a session's parse state stays small
+fn preview(text: &str) -> String {
+    if text.len() <= MAX_PREVIEW_BYTES {
+        return text.to_string();
+    }
+    let mut content = text[..text.floor_char_boundary(MAX_PREVIEW_BYTES)].to_string();
+    content.push_str("...");
+    content
+}
+
+/// Extract first text block as a single-line string
+fn extract_text(content: &MessageContent) -> String {
+    let text = match content {
+        MessageContent::Text(s) => s.as_str(),
+        MessageContent::Blocks(_) => match content.blocks("text").find_map(|b| b.text.as_deref()) {
+            Some(text) => text,
+            None => return String::new(),
+        },
+    };
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Extract raw text preserving newlines — for human contribution counting
+pub fn extract_raw_text(content: &MessageContent) -> String {
+    match content {
+        MessageContent::Text(s) => s.clone(),
+        MessageContent::Blocks(_) => content
+            .blocks("text")
+            .filter_map(|b| b.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("\n"),
     }
 }
 
@@ -328,14 +782,20 @@ pub fn strip_html(s: &str) -> String {
     result
 }
 
-/// Quick metadata extraction: reads only first few events (does NOT read the whole file)
-pub fn parse_session_metadata(file_path: &str) -> Result<(String, String)> {
-    use std::io::{BufRead, BufReader};
+/// First values found for a session file's identifying fields
+#[derive(Debug, Default)]
+pub struct SessionMetadata {
+    pub cwd: String,
+    pub started_at: String,
+    /// For subagent transcripts, the parent session's id
+    pub session_id: String,
+}
 
+/// Quick metadata extraction: reads only first few events (does NOT read the whole file)
+pub fn parse_session_metadata(file_path: &str) -> Result<SessionMetadata> {
     let file = fs::File::open(file_path)?;
     let reader = BufReader::new(file);
-    let mut cwd = String::new();
-    let mut started_at = String::new();
+    let mut meta = SessionMetadata::default();
 
     for (i, line_result) in reader.lines().enumerate() {
         if i >= 20 {
@@ -355,21 +815,26 @@ pub fn parse_session_metadata(file_path: &str) -> Result<(String, String)> {
             Err(_) => continue,
         };
 
-        if cwd.is_empty() {
+        if meta.cwd.is_empty() {
             if let Some(ref c) = event.cwd {
-                cwd = c.clone();
+                meta.cwd = c.clone();
             }
         }
-        if started_at.is_empty() {
+        if meta.started_at.is_empty() {
             if let Some(ref ts) = event.timestamp {
-                started_at = ts.clone();
+                meta.started_at = ts.clone();
+            }
+        }
+        if meta.session_id.is_empty() {
+            if let Some(ref id) = event.session_id {
+                meta.session_id = id.clone();
             }
         }
 
-        if !cwd.is_empty() && !started_at.is_empty() {
+        if !meta.cwd.is_empty() && !meta.started_at.is_empty() && !meta.session_id.is_empty() {
             break;
         }
     }
 
-    Ok((cwd, started_at))
+    Ok(meta)
 }
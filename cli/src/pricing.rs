@@ -0,0 +1,192 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::config_dir;
+use crate::models::{CostParts, TokenTotals, TokenUsage};
+
+/// Breakdown key for messages that do not report a model
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// Price key for Cursor sessions, which never name their model
+pub const CURSOR_MODEL: &str = "cursor";
+
+const DEFAULT_PRICING: &str = include_str!("default_pricing.toml");
+
+static TABLE: OnceLock<PricingTable> = OnceLock::new();
+
+/// One `[[price]]` entry; rates are dollars per million tokens
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PriceEntry {
+    model: Option<String>,
+    prefix: Option<String>,
+    input: f64,
+    output: f64,
+    cache_write_5m: Option<f64>,
+    cache_write_1h: Option<f64>,
+    cache_read: Option<f64>,
+    from: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PricingFile {
+    #[serde(default)]
+    price: Vec<PriceEntry>,
+}
+
+/// Rates for one model at one point in time, per million tokens
+struct ModelPricing {
+    input: f64,
+    output: f64,
+    cache_write_5m: f64,
+    cache_write_1h: f64,
+    cache_read: f64,
+}
+
+/// User prices layered over the built-in table
+pub struct PricingTable {
+    user: Vec<PriceEntry>,
+    builtin: Vec<PriceEntry>,
+}
+
+/// User pricing files, in lookup order
+pub fn pricing_paths() -> [PathBuf; 2] {
+    [config_dir().join("pricing.toml"), config_dir().join("pricing.json")]
+}
+
+/// Load the built-in table and the user's pricing file, if any. Call once at startup;
+/// until then (or if never called) only the built-in table is used.
+pub fn init() -> Result<()> {
+    let table = PricingTable {
+        user: load_user_entries()?,
+        builtin: builtin_entries(),
+    };
+    let _ = TABLE.set(table);
+    Ok(())
+}
+
+fn table() -> &'static PricingTable {
+    TABLE.get_or_init(|| PricingTable { user: Vec::new(), builtin: builtin_entries() })
+}
+
+fn builtin_entries() -> Vec<PriceEntry> {
+    toml::from_str::<PricingFile>(DEFAULT_PRICING)
+        .expect("built-in pricing table is valid")
+        .price
+}
+
+fn load_user_entries() -> Result<Vec<PriceEntry>> {
+    for path in pricing_paths() {
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("Reading {}", path.display())),
+        };
+
+        let file: PricingFile = if path.extension().is_some_and(|e| e == "json") {
+            serde_json::from_str(&raw).with_context(|| format!("Parsing {}", path.display()))?
+        } else {
+            toml::from_str(&raw).with_context(|| format!("Parsing {}", path.display()))?
+        };
+
+        for (i, entry) in file.price.iter().enumerate() {
+            if entry.model.is_some() == entry.prefix.is_some() {
+                bail!(
+                    "{}: price entry {} needs exactly one of `model` or `prefix`",
+                    path.display(),
+                    i + 1
+                );
+            }
+        }
+        return Ok(file.price);
+    }
+    Ok(Vec::new())
+}
+
+impl PriceEntry {
+    /// How well this entry matches, ignoring case: exact beats any prefix, longer
+    /// prefixes beat shorter
+    fn match_score(&self, model: &str, day: Option<NaiveDate>) -> Option<usize> {
+        if let Some(day) = day {
+            if self.from.is_some_and(|f| day < f) || self.until.is_some_and(|u| day >= u) {
+                return None;
+            }
+        }
+        match (&self.model, &self.prefix) {
+            (Some(m), _) if m.eq_ignore_ascii_case(model) => Some(usize::MAX),
+            (_, Some(p)) if model.get(..p.len()).is_some_and(|s| s.eq_ignore_ascii_case(p)) => {
+                Some(p.len())
+            }
+            _ => None,
+        }
+    }
+
+    fn pricing(&self) -> ModelPricing {
+        ModelPricing {
+            input: self.input,
+            output: self.output,
+            cache_write_5m: self.cache_write_5m.unwrap_or(self.input * 1.25),
+            cache_write_1h: self.cache_write_1h.unwrap_or(self.input * 2.0),
+            cache_read: self.cache_read.unwrap_or(self.input * 0.1),
+        }
+    }
+}
+
+impl PricingTable {
+    fn lookup(&self, model: &str, timestamp: &str) -> Option<ModelPricing> {
+        let day = DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|d| d.with_timezone(&Utc).date_naive());
+        [&self.user, &self.builtin].into_iter().find_map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| e.match_score(model, day).map(|s| (s, e)))
+                .max_by_key(|(s, _)| *s)
+                .map(|(_, e)| e.pricing())
+        })
+    }
+}
+
+/// Cost of a single message's usage at the rates in force at `timestamp`.
+/// `input_tokens` excludes cache reads and writes, which are priced separately.
+/// Returns None when the model has no price.
+pub fn usage_cost(model: &str, usage: &TokenUsage, timestamp: &str) -> Option<CostParts> {
+    let p = table().lookup(model, timestamp)?;
+    let cache_write_total = usage.cache_creation_input_tokens.unwrap_or(0);
+    let (write_5m, write_1h) = match usage.cache_creation {
+        Some(ref cc) => {
+            let w5 = cc.ephemeral_5m_input_tokens.unwrap_or(0);
+            let w1 = cc.ephemeral_1h_input_tokens.unwrap_or(0);
+            // Anything the split doesn't account for is billed at the default 5m rate
+            (w5 + cache_write_total.saturating_sub(w5 + w1), w1)
+        }
+        None => (cache_write_total, 0),
+    };
+
+    Some(CostParts {
+        input: per_million(usage.input_tokens.unwrap_or(0), p.input),
+        output: per_million(usage.output_tokens.unwrap_or(0), p.output),
+        cache_write: per_million(write_5m, p.cache_write_5m) + per_million(write_1h, p.cache_write_1h),
+        cache_read: per_million(usage.cache_read_input_tokens.unwrap_or(0), p.cache_read),
+    })
+}
+
+/// Cost of aggregated token totals, with cache writes at the 5m rate
+pub fn totals_cost(model: &str, tokens: &TokenTotals, timestamp: &str) -> Option<CostParts> {
+    let p = table().lookup(model, timestamp)?;
+    Some(CostParts {
+        input: per_million(tokens.input, p.input),
+        output: per_million(tokens.output, p.output),
+        cache_write: per_million(tokens.cache_creation, p.cache_write_5m),
+        cache_read: per_million(tokens.cache_read, p.cache_read),
+    })
+}
+
+fn per_million(tokens: u64, rate: f64) -> f64 {
+    tokens as f64 * rate / 1_000_000.0
+}
@@ -1,28 +1,70 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::models::{DataSource, ScannedProject, SessionFile};
 use crate::parser::parse_session_metadata;
 
-pub fn get_projects_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("~"))
-        .join(".claude")
-        .join("projects")
+/// Environment variable Claude Code uses to relocate its data dir
+pub const CLAUDE_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// File name prefix of subagent transcripts
+const AGENT_FILE_PREFIX: &str = "agent-";
+
+/// Resolve the Claude data roots to scan: `CLAUDE_CONFIG_DIR` or the default
+/// locations, plus any extra roots. Missing dirs are skipped and roots that
+/// resolve to the same place are kept once.
+pub fn claude_roots(extra: &[PathBuf]) -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("~"));
+    let defaults: Vec<PathBuf> = match std::env::var_os(CLAUDE_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => vec![PathBuf::from(dir)],
+        _ => [
+            Some(home.join(".claude")),
+            dirs::config_dir().map(|d| d.join("claude")),
+            Some(home.join(".config").join("claude")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+    };
+
+    let mut seen = HashSet::new();
+    defaults
+        .into_iter()
+        .chain(extra.iter().cloned())
+        .map(|dir| {
+            // Accept the `projects` dir itself as well as the root above it
+            if dir.file_name().is_some_and(|n| n == "projects") && !dir.join("projects").is_dir() {
+                dir.parent().map(Path::to_path_buf).unwrap_or(dir)
+            } else {
+                dir
+            }
+        })
+        .filter(|dir| projects_dir(dir).is_dir())
+        .filter(|dir| seen.insert(dir.canonicalize().unwrap_or_else(|_| dir.clone())))
+        .collect()
 }
 
-pub fn scan_claude_projects() -> Result<Vec<ScannedProject>> {
-    let projects_dir = get_projects_dir();
+pub fn projects_dir(root: &Path) -> PathBuf {
+    root.join("projects")
+}
 
-    if !projects_dir.exists() {
-        return Ok(vec![]);
+/// Scan the `projects` dir of every Claude root
+pub fn scan_claude_projects(roots: &[PathBuf]) -> Result<Vec<ScannedProject>> {
+    let mut projects = Vec::new();
+    for root in roots {
+        scan_projects_dir(&projects_dir(root), &mut projects)?;
     }
+    Ok(projects)
+}
 
-    let mut projects = Vec::new();
+fn scan_projects_dir(projects_dir: &Path, projects: &mut Vec<ScannedProject>) -> Result<()> {
+    if !projects_dir.exists() {
+        return Ok(());
+    }
 
-    let entries = fs::read_dir(&projects_dir)?;
+    let entries = fs::read_dir(projects_dir)?;
     for entry in entries {
         let entry = entry?;
         if !entry.file_type()?.is_dir() {
@@ -37,16 +79,25 @@ pub fn scan_claude_projects() -> Result<Vec<ScannedProject>> {
             for f in files {
                 let f = f?;
                 let fname = f.file_name().to_string_lossy().to_string();
-                if !fname.ends_with(".jsonl") || f.file_type()?.is_dir() {
+                if f.file_type()?.is_dir() {
+                    // Newer Claude Code versions keep subagent transcripts under the session's dir
+                    scan_subagents_dir(&f.path().join("subagents"), &fname, &mut session_files)?;
+                    continue;
+                }
+                if !fname.ends_with(".jsonl") {
                     continue;
                 }
-                let meta = f.metadata()?;
-                session_files.push(SessionFile {
-                    id: fname.trim_end_matches(".jsonl").to_string(),
-                    path: f.path().to_string_lossy().to_string(),
-                    size: meta.len(),
-                    source: DataSource::Claude,
-                });
+                let id = fname.trim_end_matches(".jsonl").to_string();
+                // Older ones write them next to the sessions, naming the parent inside
+                let parent_session = if id.starts_with(AGENT_FILE_PREFIX) {
+                    parse_session_metadata(&f.path().to_string_lossy())
+                        .ok()
+                        .map(|m| m.session_id)
+                        .filter(|s| !s.is_empty())
+                } else {
+                    None
+                };
+                session_files.push(session_file(&f, id, parent_session)?);
             }
         }
 
@@ -64,7 +115,36 @@ pub fn scan_claude_projects() -> Result<Vec<ScannedProject>> {
         }
     }
 
-    Ok(projects)
+    Ok(())
+}
+
+/// Add the `agent-*.jsonl` transcripts in a session's `subagents` dir
+fn scan_subagents_dir(dir: &Path, parent: &str, session_files: &mut Vec<SessionFile>) -> Result<()> {
+    let Ok(files) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for f in files {
+        let f = f?;
+        let fname = f.file_name().to_string_lossy().to_string();
+        if !fname.ends_with(".jsonl") || f.file_type()?.is_dir() {
+            continue;
+        }
+        let id = fname.trim_end_matches(".jsonl").to_string();
+        session_files.push(session_file(&f, id, Some(parent.to_string()))?);
+    }
+    Ok(())
+}
+
+fn session_file(f: &fs::DirEntry, id: String, parent_session: Option<String>) -> Result<SessionFile> {
+    let meta = f.metadata()?;
+    Ok(SessionFile {
+        id,
+        path: f.path().to_string_lossy().to_string(),
+        size: meta.len(),
+        modified: meta.modified().ok(),
+        source: DataSource::Claude,
+        parent_session,
+    })
 }
 
 /// Resolve a Claude project's actual filesystem path.
@@ -73,9 +153,9 @@ fn resolve_claude_project_dir(session_files: &[SessionFile], project_id: &str) -
     // Try reading cwd from the first (or most recent by name) session file
     // Session files are UUIDs, so just try the first one
     for sf in session_files.iter().take(3) {
-        if let Ok((cwd, _)) = parse_session_metadata(&sf.path) {
-            if !cwd.is_empty() {
-                return cwd;
+        if let Ok(meta) = parse_session_metadata(&sf.path) {
+            if !meta.cwd.is_empty() {
+                return meta.cwd;
             }
         }
     }
@@ -102,6 +182,7 @@ fn decode_project_id(id: &str) -> String {
 
 /// Merge Claude and Cursor projects by resolved filesystem path.
 /// Projects sharing the same path get merged into one with sources = [Claude, Cursor].
+/// The same project found under several Claude roots is merged the same way.
 pub fn scan_all_projects(
     claude_projects: Vec<ScannedProject>,
     cursor_projects: Vec<ScannedProject>,
@@ -109,27 +190,39 @@ pub fn scan_all_projects(
     // Build a map keyed by resolved path
     let mut by_path: HashMap<String, ScannedProject> = HashMap::new();
 
-    for proj in claude_projects {
+    for proj in claude_projects.into_iter().chain(cursor_projects) {
         let key = normalize_path(&proj.dir);
-        by_path.insert(key, proj);
-    }
-
-    for cursor_proj in cursor_projects {
-        let key = normalize_path(&cursor_proj.dir);
         if let Some(existing) = by_path.get_mut(&key) {
-            // Merge: add cursor sessions + update sources
-            existing.session_files.extend(cursor_proj.session_files);
-            if !existing.sources.contains(&DataSource::Cursor) {
-                existing.sources.push(DataSource::Cursor);
-            }
+            merge_project(existing, proj);
         } else {
-            by_path.insert(key, cursor_proj);
+            by_path.insert(key, proj);
         }
     }
 
     by_path.into_values().collect()
 }
 
+/// Add another scan of the same project: its sessions and sources
+fn merge_project(existing: &mut ScannedProject, other: ScannedProject) {
+    for sf in other.session_files {
+        // A session copied into two roots is counted once, from the larger file
+        match existing
+            .session_files
+            .iter_mut()
+            .find(|e| e.source == sf.source && e.id == sf.id)
+        {
+            Some(e) if sf.size > e.size => *e = sf,
+            Some(_) => {}
+            None => existing.session_files.push(sf),
+        }
+    }
+    for src in other.sources {
+        if !existing.sources.contains(&src) {
+            existing.sources.push(src);
+        }
+    }
+}
+
 fn normalize_path(path: &str) -> String {
     path.trim_end_matches('/').to_string()
 }
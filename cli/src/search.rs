@@ -0,0 +1,200 @@
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::models::{ConversationMessage, ParsedSession, ProjectSummary};
+
+/// Most hits a search returns
+pub const MAX_HITS: usize = 200;
+
+/// Bytes of context shown before the first match, and the snippet length
+const SNIPPET_BEFORE: usize = 60;
+const SNIPPET_LEN: usize = 220;
+
+/// Term occurrences beyond this add nothing to a message's score
+const MAX_TERM_COUNT: usize = 5;
+
+/// Which part of a message matched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HitField {
+    Prompt,
+    Response,
+    /// Input of a tool call, with the tool's name
+    ToolInput(String),
+}
+
+impl HitField {
+    pub fn label(&self) -> &str {
+        match self {
+            HitField::Prompt => "prompt",
+            HitField::Response => "response",
+            HitField::ToolInput(tool) => tool,
+        }
+    }
+}
+
+/// A message matching every search term
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// Index into the searched projects
+    pub project: usize,
+    /// Index into the project's sessions
+    pub session: usize,
+    /// Index into the session's subagent threads, when the message is in one
+    pub agent: Option<usize>,
+    /// Index into the (sub)session's messages
+    pub message: usize,
+    pub field: HitField,
+    pub score: usize,
+    pub timestamp: String,
+    /// Text around the first match, on one line
+    pub snippet: String,
+    /// Byte ranges of matched terms within `snippet`
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// Find messages containing every whitespace-separated term of `query`, best first.
+/// Matching ignores ASCII case. Prompts, responses and tool inputs are searched; a hit
+/// scores by how often the terms occur, more when they occur together as typed.
+pub fn search(projects: &[ProjectSummary], query: &str) -> Vec<SearchHit> {
+    let terms: Vec<String> = query.split_whitespace().map(|t| t.to_ascii_lowercase()).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let phrase = (terms.len() > 1).then(|| terms.join(" "));
+
+    let mut hits: Vec<SearchHit> = projects
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(pi, project)| {
+            let mut hits = Vec::new();
+            for (si, session) in project.sessions.iter().enumerate() {
+                search_session(session, &terms, phrase.as_deref(), &mut |mut hit| {
+                    hit.project = pi;
+                    hit.session = si;
+                    hits.push(hit);
+                });
+                for (ai, agent) in session.agents.iter().enumerate() {
+                    search_session(agent, &terms, phrase.as_deref(), &mut |mut hit| {
+                        hit.project = pi;
+                        hit.session = si;
+                        hit.agent = Some(ai);
+                        hits.push(hit);
+                    });
+                }
+            }
+            hits
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| b.timestamp.cmp(&a.timestamp)));
+
+    // Resumed sessions repeat earlier messages; show each message once
+    let mut seen: HashSet<(usize, String)> = HashSet::new();
+    hits.retain(|h| {
+        let session = &projects[h.project].sessions[h.session];
+        let session = h.agent.map_or(session, |a| &session.agents[a]);
+        let uuid = &session.messages[h.message].uuid;
+        uuid.is_empty() || seen.insert((h.project, uuid.clone()))
+    });
+    hits.truncate(MAX_HITS);
+    hits
+}
+
+fn search_session(
+    session: &ParsedSession,
+    terms: &[String],
+    phrase: Option<&str>,
+    emit: &mut dyn FnMut(SearchHit),
+) {
+    for (mi, msg) in session.messages.iter().enumerate() {
+        if msg.duplicate {
+            continue;
+        }
+        if let Some(mut hit) = match_message(msg, terms, phrase) {
+            hit.message = mi;
+            emit(hit);
+        }
+    }
+}
+
+/// Score a message against the terms; every term must occur somewhere in it
+fn match_message(msg: &ConversationMessage, terms: &[String], phrase: Option<&str>) -> Option<SearchHit> {
+    let mut fields: Vec<(HitField, &str)> = Vec::with_capacity(1 + msg.tool_uses.len());
+    if !msg.content.is_empty() {
+        let field = if msg.role == "user" { HitField::Prompt } else { HitField::Response };
+        fields.push((field, &msg.content));
+    }
+    for tool in &msg.tool_uses {
+        fields.push((HitField::ToolInput(tool.name.clone()), &tool.input));
+    }
+
+    let lowered: Vec<String> = fields.iter().map(|(_, text)| text.to_ascii_lowercase()).collect();
+    if !terms.iter().all(|t| lowered.iter().any(|l| l.contains(t.as_str()))) {
+        return None;
+    }
+
+    // Score each field; the best one supplies the snippet
+    let (best, score) = lowered
+        .iter()
+        .enumerate()
+        .map(|(i, lower)| {
+            let term_score: usize =
+                terms.iter().map(|t| lower.matches(t.as_str()).count().min(MAX_TERM_COUNT)).sum();
+            let phrase_score = phrase.map_or(0, |p| lower.matches(p).count().min(MAX_TERM_COUNT) * 3);
+            let prompt_bonus = usize::from(fields[i].0 == HitField::Prompt) * 2;
+            (i, term_score + phrase_score + prompt_bonus)
+        })
+        .max_by_key(|(_, score)| *score)?;
+
+    let (field, text) = fields.swap_remove(best);
+    let (snippet, highlights) = snippet(text, &lowered[best], terms, phrase);
+    Some(SearchHit {
+        project: 0,
+        session: 0,
+        agent: None,
+        message: 0,
+        field,
+        score,
+        timestamp: msg.timestamp.clone(),
+        snippet,
+        highlights,
+    })
+}
+
+/// One line of `text` around the first match, with the matched ranges in it.
+/// `lower` is `text` ASCII-lowercased, so byte offsets agree between the two.
+fn snippet(text: &str, lower: &str, terms: &[String], phrase: Option<&str>) -> (String, Vec<Range<usize>>) {
+    let first = phrase
+        .and_then(|p| lower.find(p))
+        .or_else(|| terms.iter().filter_map(|t| lower.find(t.as_str())).min())
+        .unwrap_or(0);
+    let start = text.floor_char_boundary(first.saturating_sub(SNIPPET_BEFORE));
+    let end = text.ceil_char_boundary((start + SNIPPET_LEN).min(text.len()));
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+    let body: String = text[start..end]
+        .chars()
+        .map(|c| if c.is_whitespace() && c.is_ascii() { ' ' } else { c })
+        .collect();
+    let snippet = format!("{}{}{}", prefix, body, suffix);
+
+    let mut highlights: Vec<Range<usize>> = Vec::new();
+    let window = &lower[start..end];
+    for term in terms {
+        for (pos, _) in window.match_indices(term.as_str()) {
+            let from = prefix.len() + pos;
+            highlights.push(from..from + term.len());
+        }
+    }
+    highlights.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(highlights.len());
+    for range in highlights {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    (snippet, merged)
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use crate::models::ToolOutcomes;
+
+/// Tool whose `command` input is a shell command line
+pub const SHELL_TOOL: &str = "Bash";
+
+/// Programs whose first argument names a subcommand (`git commit`, `cargo test`)
+const SUBCOMMAND_PROGRAMS: &[&str] = &[
+    "apt", "apt-get", "aws", "brew", "bun", "bunx", "bundle", "cargo", "composer", "conda",
+    "dart", "deno", "docker", "dotnet", "flutter", "gcloud", "gh", "git", "go", "gradle",
+    "gradlew", "helm", "just", "kubectl", "make", "mix", "mvn", "nix", "npm", "npx", "pip",
+    "pip3", "pnpm", "podman", "poetry", "rails", "rustup", "swift", "systemctl", "terraform",
+    "uv", "uvx", "yarn", "zig",
+];
+
+/// Subcommands whose next argument names the script or binary they run (`npm run build`)
+const RUNNER_SUBCOMMANDS: &[&str] = &["run", "exec", "x", "dlx"];
+
+/// Setup commands passed over in favour of the command they prepare (`cd app && make`)
+const PRELUDE_PROGRAMS: &[&str] = &["cd", "pushd", "popd", "export", "source", ".", "set"];
+
+/// Wrappers that run the command following their own options (`sudo`, `timeout 60`)
+const WRAPPER_PROGRAMS: &[&str] = &["sudo", "time", "env", "nohup", "exec", "command", "timeout"];
+
+/// Wrapper options taking a separate value (`sudo -u postgres psql`)
+const WRAPPER_OPTIONS_WITH_VALUE: &[(&str, &[&str])] = &[
+    ("sudo", &["-u", "-g", "-C", "-D", "-h", "-p", "-r", "-t", "-U", "--user", "--group", "--chdir"]),
+    ("timeout", &["-s", "-k", "--signal", "--kill-after"]),
+    ("env", &["-u", "-C", "-S", "--unset", "--chdir", "--split-string"]),
+    ("time", &["-f", "-o", "--format", "--output"]),
+];
+
+/// Options taking a separate value before the subcommand (`git -C dir status`)
+const OPTIONS_WITH_VALUE: &[&str] = &["-C", "-c", "--git-dir", "--work-tree", "--prefix", "--cwd"];
+
+/// The program and subcommand a shell command line runs, e.g. `git commit` for
+/// `cd repo && git commit -m "msg"`. Returns None for an empty command.
+pub fn command_key(command: &str) -> Option<String> {
+    let segments = split_segments(command);
+    let words = segments
+        .iter()
+        .find(|words| !PRELUDE_PROGRAMS.contains(&program_name(&words[0])))
+        .or(segments.first())?;
+    let mut words = words.iter().map(String::as_str).peekable();
+
+    let program = loop {
+        let word = words.next()?;
+        if is_assignment(word) {
+            continue;
+        }
+        let name = program_name(word);
+        if WRAPPER_PROGRAMS.contains(&name) {
+            let with_value = WRAPPER_OPTIONS_WITH_VALUE
+                .iter()
+                .find(|(wrapper, _)| *wrapper == name)
+                .map_or(&[][..], |(_, options)| *options);
+            while let Some(option) = words
+                .next_if(|w| w.starts_with('-') || is_assignment(w) || w.starts_with(|c: char| c.is_ascii_digit()))
+            {
+                if with_value.contains(&option) {
+                    words.next();
+                }
+            }
+            continue;
+        }
+        break name;
+    };
+
+    let mut key = program.to_string();
+    if !SUBCOMMAND_PROGRAMS.contains(&program) {
+        return Some(key);
+    }
+    while let Some(word) = words.next_if(|w| w.starts_with('-') || w.starts_with('+')) {
+        if OPTIONS_WITH_VALUE.contains(&word) {
+            words.next();
+        }
+    }
+    if let Some(sub) = words.next().filter(|w| is_name(w)) {
+        key.push(' ');
+        key.push_str(sub);
+        if RUNNER_SUBCOMMANDS.contains(&sub) {
+            if let Some(target) = words.next().filter(|w| is_name(w)) {
+                key.push(' ');
+                key.push_str(target);
+            }
+        }
+    }
+    Some(key)
+}
+
+/// The program part of a command key (`git` for `git commit`)
+pub fn program_of(key: &str) -> &str {
+    key.split(' ').next().unwrap_or(key)
+}
+
+/// Roll command usage and outcomes up to one entry per program
+pub fn group_by_program(
+    usage: &HashMap<String, u64>,
+    outcomes: &HashMap<String, ToolOutcomes>,
+) -> (HashMap<String, u64>, HashMap<String, ToolOutcomes>) {
+    let mut by_usage: HashMap<String, u64> = HashMap::new();
+    for (key, count) in usage {
+        *by_usage.entry(program_of(key).to_string()).or_insert(0) += count;
+    }
+    let mut by_outcome: HashMap<String, ToolOutcomes> = HashMap::new();
+    for (key, outcome) in outcomes {
+        by_outcome.entry(program_of(key).to_string()).or_default().add(outcome);
+    }
+    (by_usage, by_outcome)
+}
+
+/// Split a command line into the words of each simple command, honouring quotes.
+/// `;`, `&`, `|`, newlines and parentheses end a command.
+fn split_segments(command: &str) -> Vec<Vec<String>> {
+    let mut segments: Vec<Vec<String>> = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '\\') => {
+                if let Some(next) = chars.next().filter(|&n| n != '\n') {
+                    word.push(next);
+                    in_word = true;
+                }
+            }
+            // Redirections like `2>&1` and `&>log` stay part of the command
+            (None, '&') if word.ends_with('>') || chars.peek() == Some(&'>') => {
+                word.push(c);
+                in_word = true;
+            }
+            (None, ';' | '&' | '|' | '\n' | '(' | ')') => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+                if !words.is_empty() {
+                    segments.push(std::mem::take(&mut words));
+                }
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    if !words.is_empty() {
+        segments.push(words);
+    }
+    segments
+}
+
+/// `/usr/bin/git` and `./gradlew` run `git` and `gradlew`
+fn program_name(word: &str) -> &str {
+    word.rsplit('/').next().unwrap_or(word)
+}
+
+/// `NAME=value` prefix setting an environment variable
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// Looks like a subcommand or script name rather than a path, file or value
+fn is_name(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+}
@@ -1,8 +1,14 @@
+use ratatui::text::Line;
 use ratatui::widgets::TableState;
+use std::collections::HashSet;
 use std::sync::mpsc;
 
-use crate::models::{GlobalMetrics, ProjectSummary};
+use crate::cursor_parser::read_full_bubble;
+use crate::models::{ConversationMessage, DataSource, GlobalMetrics, ParsedSession, ProjectSummary};
+use crate::parser::read_full_message;
+use crate::search::{search, SearchHit};
 use crate::theme::{load_saved_theme, Theme};
+use crate::window::TimeWindow;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum View {
@@ -10,12 +16,18 @@ pub enum View {
     ProjectList,
     ProjectDetail,
     SessionDetail,
+    /// Full-text search across every conversation
+    Search,
+    /// One message of the thread in full
+    Message,
 }
 
 /// Messages from background data loading
 pub enum LoadMessage {
     Progress(String),
     Done(Vec<ProjectSummary>, GlobalMetrics),
+    /// Fresh data after files changed on disk
+    Updated(Vec<ProjectSummary>, GlobalMetrics),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,7 +36,7 @@ pub enum InputMode {
     Search,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum SortColumn {
     Name,
     Sessions,
@@ -59,6 +71,136 @@ impl SortColumn {
             SortColumn::LastActive => "Last Active",
         }
     }
+
+    /// Whether the column first sorts ascending (only name does)
+    pub fn ascending_by_default(self) -> bool {
+        self == SortColumn::Name
+    }
+
+    /// Compare two projects by this column (descending except for name)
+    pub fn compare(self, pa: &ProjectSummary, pb: &ProjectSummary) -> std::cmp::Ordering {
+        let cmp = match self {
+            SortColumn::Name => pa.name.to_lowercase().cmp(&pb.name.to_lowercase()),
+            SortColumn::Sessions => pa.session_count.cmp(&pb.session_count),
+            SortColumn::Messages => pa.message_count.cmp(&pb.message_count),
+            SortColumn::Tokens => pa.total_tokens.total().cmp(&pb.total_tokens.total()),
+            SortColumn::Lines => (pa.lines_added + pa.lines_removed)
+                .cmp(&(pb.lines_added + pb.lines_removed)),
+            SortColumn::Cost => pa.cost.partial_cmp(&pb.cost).unwrap_or(std::cmp::Ordering::Equal),
+            SortColumn::LastActive => pa.last_active.cmp(&pb.last_active),
+        };
+        // Default descending except for name
+        match self {
+            SortColumn::Name => cmp,
+            _ => cmp.reverse(),
+        }
+    }
+}
+
+/// Columns the session table in ProjectDetail sorts by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionSort {
+    /// Load order: sessions of a thread together, most recently active thread first
+    Thread,
+    Started,
+    Cost,
+    Tokens,
+    Duration,
+    Lines,
+    Messages,
+    Model,
+}
+
+impl SessionSort {
+    pub fn next(self) -> Self {
+        match self {
+            SessionSort::Thread => SessionSort::Started,
+            SessionSort::Started => SessionSort::Cost,
+            SessionSort::Cost => SessionSort::Tokens,
+            SessionSort::Tokens => SessionSort::Duration,
+            SessionSort::Duration => SessionSort::Lines,
+            SessionSort::Lines => SessionSort::Messages,
+            SessionSort::Messages => SessionSort::Model,
+            SessionSort::Model => SessionSort::Thread,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionSort::Thread => "Thread",
+            SessionSort::Started => "Started",
+            SessionSort::Cost => "Cost",
+            SessionSort::Tokens => "Tokens",
+            SessionSort::Duration => "Duration",
+            SessionSort::Lines => "Lines",
+            SessionSort::Messages => "Messages",
+            SessionSort::Model => "Model",
+        }
+    }
+
+    /// Whether the column first sorts ascending; amounts and dates start with the largest
+    pub fn ascending_by_default(self) -> bool {
+        matches!(self, SessionSort::Thread | SessionSort::Model)
+    }
+
+    /// Compare two sessions by this column, ascending
+    pub fn compare(self, a: &ParsedSession, b: &ParsedSession) -> std::cmp::Ordering {
+        match self {
+            SessionSort::Thread => std::cmp::Ordering::Equal,
+            SessionSort::Started => a.started_at.cmp(&b.started_at),
+            SessionSort::Cost => a.cost.partial_cmp(&b.cost).unwrap_or(std::cmp::Ordering::Equal),
+            SessionSort::Tokens => a.total_tokens.total().cmp(&b.total_tokens.total()),
+            SessionSort::Duration => a
+                .duration_ms
+                .partial_cmp(&b.duration_ms)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            SessionSort::Lines => (a.lines_added + a.lines_removed).cmp(&(b.lines_added + b.lines_removed)),
+            SessionSort::Messages => a.messages.len().cmp(&b.messages.len()),
+            SessionSort::Model => a.model.cmp(&b.model),
+        }
+    }
+}
+
+/// Which sessions the session table in ProjectDetail shows
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// Matched against the first prompt, summary and session id, ignoring case
+    pub text: String,
+    pub model: Option<String>,
+    pub source: Option<DataSource>,
+}
+
+impl SessionFilter {
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty() || self.model.is_some() || self.source.is_some()
+    }
+
+    pub fn matches(&self, session: &ParsedSession) -> bool {
+        if self.model.as_ref().is_some_and(|m| *m != session.model)
+            || self.source.is_some_and(|s| s != session.source)
+        {
+            return false;
+        }
+        let text = self.text.to_lowercase();
+        text.is_empty()
+            || session.first_prompt.to_lowercase().contains(&text)
+            || session.summary.to_lowercase().contains(&text)
+            || session.session_id.starts_with(&text)
+    }
+}
+
+/// A message read back in full from its source for the message view
+pub struct MessageView {
+    pub message: ConversationMessage,
+    /// Why the full text couldn't be read; the stored preview is shown instead
+    pub error: Option<String>,
+    /// Position of the message in its thread, and the thread's length
+    pub index: usize,
+    pub total: usize,
+    /// First wrapped line shown
+    pub scroll: usize,
+    /// Markdown of the text, rendered once for the theme it was drawn in
+    pub rendered: Option<(Theme, Vec<Line<'static>>)>,
 }
 
 pub struct App {
@@ -70,20 +212,43 @@ pub struct App {
     pub input_mode: InputMode,
     pub search_query: String,
     pub sort_column: SortColumn,
-    #[allow(dead_code)]
     pub sort_ascending: bool,
+    // Session table in ProjectDetail: its own sort and filter, and the sessions they leave
+    pub session_sort: SessionSort,
+    pub session_sort_ascending: bool,
+    pub session_filter: SessionFilter,
+    pub filtered_sessions: Vec<usize>, // indices into the current project's sessions
     pub theme: Theme,
     pub project_table_state: TableState,
     pub session_table_state: TableState,
     pub selected_project: usize, // index into filtered_projects
-    pub selected_session: usize,
+    pub selected_session: usize, // index into filtered_sessions
     pub should_quit: bool,
     // Message scroll state
     pub message_scroll: usize,
+    // Subagent threads of the selected session: highlighted one, and the one being viewed
+    pub selected_agent: usize,
+    pub open_agent: Option<usize>,
+    // Show thinking text in the message thread instead of one collapsed line
+    pub expand_thinking: bool,
+    // Tool call rows in the thread: whether rows start expanded, and the calls (by tool
+    // use id) toggled the other way
+    pub expand_tools: bool,
+    pub toggled_tools: HashSet<String>,
+    // Full-text search: query, ranked hits, and the message a hit opened (session id, index)
+    pub find_query: String,
+    pub find_hits: Vec<SearchHit>,
+    pub selected_hit: usize,
+    pub find_table_state: TableState,
+    pub focused_message: Option<(String, usize)>,
+    // Message opened in full; only this one is held untruncated
+    pub message_view: Option<MessageView>,
     // Async loading
     pub loading: bool,
     pub loading_status: String,
     pub load_receiver: Option<mpsc::Receiver<LoadMessage>>,
+    // Time window the data was clipped to
+    pub window: TimeWindow,
 }
 
 impl App {
@@ -105,6 +270,10 @@ impl App {
             search_query: String::new(),
             sort_column: SortColumn::LastActive,
             sort_ascending: false,
+            session_sort: SessionSort::Thread,
+            session_sort_ascending: true,
+            session_filter: SessionFilter::default(),
+            filtered_sessions: Vec::new(),
             theme: load_saved_theme(),
             project_table_state: table_state,
             session_table_state: TableState::default(),
@@ -113,14 +282,26 @@ impl App {
             should_quit: false,
 
             message_scroll: 0,
+            selected_agent: 0,
+            open_agent: None,
+            expand_thinking: false,
+            expand_tools: false,
+            toggled_tools: HashSet::new(),
+            find_query: String::new(),
+            find_hits: Vec::new(),
+            selected_hit: 0,
+            find_table_state: TableState::default(),
+            focused_message: None,
+            message_view: None,
             loading: false,
             loading_status: String::new(),
             load_receiver: None,
+            window: TimeWindow::default(),
         }
     }
 
     /// Create an app in loading state
-    pub fn loading(rx: mpsc::Receiver<LoadMessage>) -> Self {
+    pub fn loading(rx: mpsc::Receiver<LoadMessage>, window: TimeWindow) -> Self {
         Self {
             projects: Vec::new(),
             filtered_projects: Vec::new(),
@@ -131,6 +312,10 @@ impl App {
             search_query: String::new(),
             sort_column: SortColumn::LastActive,
             sort_ascending: false,
+            session_sort: SessionSort::Thread,
+            session_sort_ascending: true,
+            session_filter: SessionFilter::default(),
+            filtered_sessions: Vec::new(),
             theme: load_saved_theme(),
             project_table_state: TableState::default(),
             session_table_state: TableState::default(),
@@ -139,39 +324,108 @@ impl App {
             should_quit: false,
 
             message_scroll: 0,
+            selected_agent: 0,
+            open_agent: None,
+            expand_thinking: false,
+            expand_tools: false,
+            toggled_tools: HashSet::new(),
+            find_query: String::new(),
+            find_hits: Vec::new(),
+            selected_hit: 0,
+            find_table_state: TableState::default(),
+            focused_message: None,
+            message_view: None,
             loading: true,
             loading_status: "Starting...".to_string(),
             load_receiver: Some(rx),
+            window,
         }
     }
 
-    /// Check if background loading has completed or has progress updates
+    /// Check for loading progress, the initial load, or live updates
     pub fn poll_load(&mut self) {
-        if !self.loading {
+        let Some(ref rx) = self.load_receiver else {
             return;
-        }
-        if let Some(ref rx) = self.load_receiver {
-            // Drain all available messages
-            while let Ok(msg) = rx.try_recv() {
-                match msg {
-                    LoadMessage::Progress(status) => {
-                        self.loading_status = status;
-                    }
-                    LoadMessage::Done(projects, metrics) => {
-                        let filtered: Vec<usize> = (0..projects.len()).collect();
-                        self.projects = projects;
-                        self.filtered_projects = filtered;
-                        self.metrics = metrics;
-                        self.loading = false;
-                        self.load_receiver = None;
-                        if !self.projects.is_empty() {
-                            self.project_table_state.select(Some(0));
-                        }
-                        return;
+        };
+
+        // Drain all available messages, keeping only the newest update
+        let mut latest = None;
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                LoadMessage::Progress(status) => {
+                    self.loading_status = status;
+                }
+                LoadMessage::Done(projects, metrics) => {
+                    let filtered: Vec<usize> = (0..projects.len()).collect();
+                    self.projects = projects;
+                    self.filtered_projects = filtered;
+                    self.metrics = metrics;
+                    self.loading = false;
+                    if !self.projects.is_empty() {
+                        self.project_table_state.select(Some(0));
                     }
                 }
+                LoadMessage::Updated(projects, metrics) => {
+                    latest = Some((projects, metrics));
+                }
             }
         }
+
+        if let Some((projects, metrics)) = latest {
+            self.replace_data(projects, metrics);
+        }
+    }
+
+    /// Swap in refreshed data while keeping the selected project, session and scroll position
+    fn replace_data(&mut self, projects: Vec<ProjectSummary>, metrics: GlobalMetrics) {
+        let project_id = self.current_project().map(|p| p.id.clone());
+        let session_id = self.selected_session_summary().map(|s| s.session_id.clone());
+
+        self.projects = projects;
+        self.metrics = metrics;
+        self.refilter();
+
+        self.selected_project = project_id
+            .and_then(|id| {
+                self.filtered_projects
+                    .iter()
+                    .position(|&i| self.projects[i].id == id)
+            })
+            .unwrap_or(self.selected_project)
+            .min(self.filtered_projects.len().saturating_sub(1));
+        self.project_table_state.select(if self.filtered_projects.is_empty() {
+            None
+        } else {
+            Some(self.selected_project)
+        });
+
+        let previous = self.selected_session;
+        self.refilter_sessions();
+        let sessions = self.current_project().map(|p| &p.sessions[..]).unwrap_or(&[]);
+        let selected = session_id
+            .and_then(|id| {
+                self.filtered_sessions
+                    .iter()
+                    .position(|&i| sessions[i].session_id == id)
+            })
+            .unwrap_or(previous)
+            .min(self.filtered_sessions.len().saturating_sub(1));
+        if selected != previous {
+            self.close_agents();
+        }
+        self.selected_session = selected;
+        let agent_count = self.selected_session_summary().map(|s| s.agents.len()).unwrap_or(0);
+        self.selected_agent = self.selected_agent.min(agent_count.saturating_sub(1));
+        self.open_agent = self.open_agent.filter(|&i| i < agent_count);
+
+        let message_count = self.current_session().map(|s| s.messages.len()).unwrap_or(0);
+        self.message_scroll = self.message_scroll.min(message_count.saturating_sub(1));
+        self.session_table_state.select(Some(self.selected_session));
+
+        // Hits index into the old data
+        let selected_hit = self.selected_hit;
+        self.run_find();
+        self.select_hit(selected_hit);
     }
 
     pub fn navigate_to(&mut self, view: View) {
@@ -181,8 +435,13 @@ impl App {
 
     pub fn go_back(&mut self) {
         if let Some(prev) = self.view_stack.pop() {
+            // Leaving a message returns to the same place in its thread
+            if self.view != View::Message {
+                self.message_scroll = 0;
+                self.focused_message = None;
+            }
+            self.message_view = None;
             self.view = prev;
-            self.message_scroll = 0;
         }
     }
 
@@ -192,19 +451,190 @@ impl App {
             .and_then(|&idx| self.projects.get(idx))
     }
 
+    /// The session selected in the project's session list
+    pub fn selected_session_summary(&self) -> Option<&ParsedSession> {
+        let idx = *self.filtered_sessions.get(self.selected_session)?;
+        self.current_project().and_then(|p| p.sessions.get(idx))
+    }
+
+    /// The session whose thread is shown: the open subagent thread, else the selected session
+    pub fn current_session(&self) -> Option<&ParsedSession> {
+        let session = self.selected_session_summary()?;
+        match self.open_agent {
+            Some(i) => session.agents.get(i),
+            None => Some(session),
+        }
+    }
+
+    /// Highlight the next subagent thread of the selected session, wrapping around
+    pub fn cycle_agent(&mut self) {
+        let count = self.selected_session_summary().map(|s| s.agents.len()).unwrap_or(0);
+        if count > 0 {
+            self.selected_agent = (self.selected_agent + 1) % count;
+        }
+    }
+
+    /// View the highlighted subagent thread
+    pub fn open_selected_agent(&mut self) {
+        let count = self.selected_session_summary().map(|s| s.agents.len()).unwrap_or(0);
+        if self.selected_agent < count {
+            self.open_agent = Some(self.selected_agent);
+            self.message_scroll = 0;
+        }
+    }
+
+    /// Return from a subagent thread to its parent. Returns false if none was open.
+    pub fn close_agent(&mut self) -> bool {
+        if self.open_agent.take().is_some() {
+            self.message_scroll = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether a tool call's row in the thread shows its input and output
+    pub fn tool_expanded(&self, id: &str) -> bool {
+        self.expand_tools != self.toggled_tools.contains(id)
+    }
+
+    /// Expand the tool calls of the message at the top of the thread, or collapse them
+    /// if they all are expanded
+    pub fn toggle_message_tools(&mut self) {
+        let Some(msg) = self.current_session().and_then(|s| s.messages.get(self.message_scroll)) else {
+            return;
+        };
+        let ids: Vec<String> = msg.tool_uses.iter().map(|t| t.id.clone()).collect();
+        let expand = !ids.iter().all(|id| self.tool_expanded(id));
+        for id in ids {
+            if expand == self.expand_tools {
+                self.toggled_tools.remove(&id);
+            } else {
+                self.toggled_tools.insert(id);
+            }
+        }
+    }
+
+    /// Expand or collapse every tool call row
+    pub fn toggle_all_tools(&mut self) {
+        self.expand_tools = !self.expand_tools;
+        self.toggled_tools.clear();
+    }
+
+    /// Forget the subagent selection, e.g. when another session is shown
+    pub fn close_agents(&mut self) {
+        self.selected_agent = 0;
+        self.open_agent = None;
+    }
+
+    /// Show the message at the top of the thread in full, read back from the session's
+    /// transcript or Cursor database. Falls back to the stored preview if that fails.
+    pub fn open_message(&mut self) {
+        let Some(session) = self.current_session() else {
+            return;
+        };
+        let Some(message) = session.messages.get(self.message_scroll) else {
+            return;
+        };
+        let full = match session.source {
+            DataSource::Claude => read_full_message(&session.source_path, message),
+            DataSource::Cursor => read_full_bubble(&session.source_path, &session.session_id, message),
+        };
+        let (message, error) = match full {
+            Ok(full) => (full, None),
+            Err(e) => (message.clone(), Some(e.to_string())),
+        };
+        self.message_view = Some(MessageView {
+            message,
+            error,
+            index: self.message_scroll,
+            total: session.messages.len(),
+            scroll: 0,
+            rendered: None,
+        });
+        self.navigate_to(View::Message);
+    }
+
+    /// Scroll the open message by `delta` lines; drawing clamps it to the text
+    pub fn scroll_message_view(&mut self, delta: isize) {
+        if let Some(view) = self.message_view.as_mut() {
+            view.scroll = view.scroll.saturating_add_signed(delta);
+        }
+    }
+
+    /// Show the search view, keeping the previous query and hits
+    pub fn open_find(&mut self) {
+        if self.view != View::Search {
+            self.navigate_to(View::Search);
+        }
+    }
+
+    /// Rerun the search for the current query
+    pub fn run_find(&mut self) {
+        self.find_hits = search(&self.projects, &self.find_query);
+        self.select_hit(0);
+    }
+
+    fn select_hit(&mut self, index: usize) {
+        self.selected_hit = index.min(self.find_hits.len().saturating_sub(1));
+        self.find_table_state.select(if self.find_hits.is_empty() {
+            None
+        } else {
+            Some(self.selected_hit)
+        });
+    }
+
+    /// Open the session of the selected hit, scrolled to the matching message
+    pub fn open_selected_hit(&mut self) {
+        let Some(hit) = self.find_hits.get(self.selected_hit).cloned() else {
+            return;
+        };
+
+        // The project filter may hide the hit's project
+        if !self.filtered_projects.contains(&hit.project) {
+            self.search_query.clear();
+            self.refilter();
+        }
+        let Some(position) = self.filtered_projects.iter().position(|&i| i == hit.project) else {
+            return;
+        };
+        self.selected_project = position;
+        self.project_table_state.select(Some(position));
+
+        // Likewise the session filter may hide the hit's session
+        self.refilter_sessions();
+        if !self.filtered_sessions.contains(&hit.session) {
+            self.session_filter = SessionFilter::default();
+            self.refilter_sessions();
+        }
+        let Some(row) = self.filtered_sessions.iter().position(|&i| i == hit.session) else {
+            return;
+        };
+        self.selected_session = row;
+        self.session_table_state.select(Some(row));
+        self.close_agents();
+        if let Some(agent) = hit.agent {
+            self.selected_agent = agent;
+            self.open_agent = Some(agent);
+        }
+
+        self.navigate_to(View::SessionDetail);
+        self.message_scroll = hit.message;
+        self.focused_message = self.current_session().map(|s| (s.session_id.clone(), hit.message));
+    }
+
     pub fn move_up(&mut self) {
         match self.view {
-            View::ProjectList => {
-                if self.selected_project > 0 {
-                    self.selected_project -= 1;
-                    self.project_table_state.select(Some(self.selected_project));
-                }
+            View::ProjectList if self.selected_project > 0 => {
+                self.selected_project -= 1;
+                self.project_table_state.select(Some(self.selected_project));
             }
-            View::ProjectDetail | View::SessionDetail => {
-                if self.selected_session > 0 {
-                    self.selected_session -= 1;
-                    self.session_table_state.select(Some(self.selected_session));
-                }
+            View::ProjectDetail | View::SessionDetail if self.selected_session > 0 => {
+                self.selected_session -= 1;
+                self.session_table_state.select(Some(self.selected_session));
+            }
+            View::Search if self.selected_hit > 0 => {
+                self.select_hit(self.selected_hit - 1);
             }
             _ => {}
         }
@@ -220,14 +650,15 @@ impl App {
                 }
             }
             View::ProjectDetail | View::SessionDetail => {
-                if let Some(proj) = self.current_project() {
-                    let max = proj.sessions.len().saturating_sub(1);
-                    if self.selected_session < max {
-                        self.selected_session += 1;
-                        self.session_table_state.select(Some(self.selected_session));
-                    }
+                let max = self.filtered_sessions.len().saturating_sub(1);
+                if self.selected_session < max {
+                    self.selected_session += 1;
+                    self.session_table_state.select(Some(self.selected_session));
                 }
             }
+            View::Search => {
+                self.select_hit(self.selected_hit + 1);
+            }
             _ => {}
         }
     }
@@ -266,11 +697,9 @@ impl App {
                 self.project_table_state.select(Some(max));
             }
             View::ProjectDetail | View::SessionDetail => {
-                if let Some(proj) = self.current_project() {
-                    let max = proj.sessions.len().saturating_sub(1);
-                    self.selected_session = max;
-                    self.session_table_state.select(Some(max));
-                }
+                let max = self.filtered_sessions.len().saturating_sub(1);
+                self.selected_session = max;
+                self.session_table_state.select(Some(max));
             }
             _ => {}
         }
@@ -281,20 +710,14 @@ impl App {
             View::Dashboard => {
                 self.navigate_to(View::ProjectList);
             }
-            View::ProjectList => {
-                if self.current_project().is_some() {
-                    self.selected_session = 0;
-                    self.session_table_state.select(Some(0));
-                    self.navigate_to(View::ProjectDetail);
-                }
+            View::ProjectList if self.current_project().is_some() => {
+                self.open_project_sessions();
+                self.navigate_to(View::ProjectDetail);
             }
-            View::ProjectDetail => {
-                if let Some(proj) = self.current_project() {
-                    if !proj.sessions.is_empty() {
-                        self.message_scroll = 0;
-                        self.navigate_to(View::SessionDetail);
-                    }
-                }
+            View::ProjectDetail if self.selected_session_summary().is_some() => {
+                self.message_scroll = 0;
+                self.close_agents();
+                self.navigate_to(View::SessionDetail);
             }
             _ => {}
         }
@@ -312,7 +735,33 @@ impl App {
         }
     }
 
+    /// The filter text edited in search mode: the session filter's in ProjectDetail,
+    /// the project filter's elsewhere
+    pub fn filter_query(&mut self) -> &mut String {
+        if self.view == View::ProjectDetail {
+            &mut self.session_filter.text
+        } else {
+            &mut self.search_query
+        }
+    }
+
     pub fn apply_filter(&mut self) {
+        if self.view == View::ProjectDetail {
+            self.apply_session_filter();
+            return;
+        }
+        self.refilter();
+
+        self.selected_project = 0;
+        if !self.filtered_projects.is_empty() {
+            self.project_table_state.select(Some(0));
+        } else {
+            self.project_table_state.select(None);
+        }
+    }
+
+    /// Recompute the filtered, sorted project list without touching the selection
+    fn refilter(&mut self) {
         let query = self.search_query.to_lowercase();
         self.filtered_projects = if query.is_empty() {
             (0..self.projects.len()).collect()
@@ -329,41 +778,115 @@ impl App {
         };
 
         self.apply_sort();
-
-        self.selected_project = 0;
-        if !self.filtered_projects.is_empty() {
-            self.project_table_state.select(Some(0));
-        } else {
-            self.project_table_state.select(None);
-        }
     }
 
     pub fn cycle_sort(&mut self) {
+        if self.view == View::ProjectDetail {
+            self.session_sort = self.session_sort.next();
+            self.session_sort_ascending = self.session_sort.ascending_by_default();
+            self.apply_session_filter();
+            return;
+        }
         self.sort_column = self.sort_column.next();
+        self.sort_ascending = self.sort_column.ascending_by_default();
+        self.apply_sort();
+    }
+
+    /// Flip the sort direction of the current view's table
+    pub fn reverse_sort(&mut self) {
+        if self.view == View::ProjectDetail {
+            self.session_sort_ascending = !self.session_sort_ascending;
+            self.apply_session_filter();
+            return;
+        }
+        self.sort_ascending = !self.sort_ascending;
         self.apply_sort();
     }
 
     fn apply_sort(&mut self) {
         let projects = &self.projects;
         let col = self.sort_column;
+        let reverse = self.sort_ascending != col.ascending_by_default();
         self.filtered_projects.sort_by(|&a, &b| {
-            let pa = &projects[a];
-            let pb = &projects[b];
-            let cmp = match col {
-                SortColumn::Name => pa.name.to_lowercase().cmp(&pb.name.to_lowercase()),
-                SortColumn::Sessions => pa.session_count.cmp(&pb.session_count),
-                SortColumn::Messages => pa.message_count.cmp(&pb.message_count),
-                SortColumn::Tokens => pa.total_tokens.total().cmp(&pb.total_tokens.total()),
-                SortColumn::Lines => (pa.lines_added + pa.lines_removed)
-                    .cmp(&(pb.lines_added + pb.lines_removed)),
-                SortColumn::Cost => pa.cost.partial_cmp(&pb.cost).unwrap_or(std::cmp::Ordering::Equal),
-                SortColumn::LastActive => pa.last_active.cmp(&pb.last_active),
-            };
-            // Default descending except for name
-            match col {
-                SortColumn::Name => cmp,
-                _ => cmp.reverse(),
-            }
+            let cmp = col.compare(&projects[a], &projects[b]);
+            if reverse { cmp.reverse() } else { cmp }
+        });
+    }
+
+    /// Show the current project's sessions from the top, keeping the sort but not
+    /// a filter picked for another project
+    pub fn open_project_sessions(&mut self) {
+        self.session_filter = SessionFilter::default();
+        self.apply_session_filter();
+    }
+
+    /// Show only sessions of the next model used in the project, then all again
+    pub fn cycle_model_filter(&mut self) {
+        let Some(project) = self.current_project() else {
+            return;
+        };
+        let mut models: Vec<&String> = project.sessions.iter().map(|s| &s.model).collect();
+        models.sort();
+        models.dedup();
+        let next = match &self.session_filter.model {
+            None => models.first(),
+            Some(current) => models.iter().skip_while(|m| **m != current).nth(1),
+        };
+        self.session_filter.model = next.map(|m| (*m).clone());
+        self.apply_session_filter();
+    }
+
+    /// Show only Claude sessions, then only Cursor sessions, then all again
+    pub fn cycle_source_filter(&mut self) {
+        self.session_filter.source = match self.session_filter.source {
+            None => Some(DataSource::Claude),
+            Some(DataSource::Claude) => Some(DataSource::Cursor),
+            Some(DataSource::Cursor) => None,
+        };
+        self.apply_session_filter();
+    }
+
+    /// Bring the session table up to date with the selected project, keeping the
+    /// selected row where it still exists
+    pub fn sync_sessions(&mut self) {
+        self.refilter_sessions();
+        self.selected_session = self.selected_session.min(self.filtered_sessions.len().saturating_sub(1));
+        self.session_table_state.select(if self.filtered_sessions.is_empty() {
+            None
+        } else {
+            Some(self.selected_session)
+        });
+    }
+
+    /// Refilter the session table and select its first row
+    fn apply_session_filter(&mut self) {
+        self.refilter_sessions();
+        self.selected_session = 0;
+        self.session_table_state.select(if self.filtered_sessions.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    /// Recompute the filtered, sorted session list without touching the selection
+    fn refilter_sessions(&mut self) {
+        let Some(&project) = self.filtered_projects.get(self.selected_project) else {
+            self.filtered_sessions.clear();
+            return;
+        };
+        let sessions = &self.projects[project].sessions;
+        let filter = &self.session_filter;
+        self.filtered_sessions = (0..sessions.len())
+            .filter(|&i| filter.matches(&sessions[i]))
+            .collect();
+
+        let col = self.session_sort;
+        let reverse = self.session_sort_ascending != col.ascending_by_default();
+        self.filtered_sessions.sort_by(|&a, &b| {
+            // Ties keep load order, so reversing the thread order reverses the table
+            let cmp = col.compare(&sessions[a], &sessions[b]).then(a.cmp(&b));
+            if reverse { cmp.reverse() } else { cmp }
         });
     }
 }
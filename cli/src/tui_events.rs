@@ -31,25 +31,74 @@ fn handle_search_input(app: &mut App, code: KeyCode) {
     match code {
         KeyCode::Esc => {
             app.input_mode = InputMode::Normal;
-            app.search_query.clear();
+            app.filter_query().clear();
             app.apply_filter();
         }
         KeyCode::Enter => {
             app.input_mode = InputMode::Normal;
         }
         KeyCode::Backspace => {
-            app.search_query.pop();
+            app.filter_query().pop();
             app.apply_filter();
         }
         KeyCode::Char(c) => {
-            app.search_query.push(c);
+            app.filter_query().push(c);
             app.apply_filter();
         }
         _ => {}
     }
 }
 
+/// In the search view, typing edits the query and the arrows pick a hit
+fn handle_find_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Esc => app.go_back(),
+        KeyCode::Enter => app.open_selected_hit(),
+        KeyCode::Up => app.move_up(),
+        KeyCode::Down => app.move_down(),
+        KeyCode::PageUp => app.page_up(),
+        KeyCode::PageDown => app.page_down(),
+        KeyCode::Backspace => {
+            app.find_query.pop();
+            app.run_find();
+        }
+        KeyCode::Char(c) => {
+            app.find_query.push(c);
+            app.run_find();
+        }
+        _ => {}
+    }
+}
+
+/// In the message view, the keys scroll the message's text
+fn handle_message_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_message_view(1),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_message_view(-1),
+        KeyCode::Char('d') | KeyCode::PageDown => app.scroll_message_view(20),
+        KeyCode::Char('u') | KeyCode::PageUp => app.scroll_message_view(-20),
+        KeyCode::Char('g') => app.scroll_message_view(isize::MIN),
+        KeyCode::Char('G') => app.scroll_message_view(isize::MAX),
+        KeyCode::Esc | KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => app.go_back(),
+        KeyCode::Char('t') => {
+            app.theme = app.theme.next();
+            save_theme(app.theme);
+        }
+        _ => {}
+    }
+}
+
 fn handle_normal_input(app: &mut App, code: KeyCode) {
+    if app.view == View::Search {
+        handle_find_input(app, code);
+        return;
+    }
+    if app.view == View::Message {
+        handle_message_input(app, code);
+        return;
+    }
+
     // In SessionDetail, j/k scroll through messages
     if app.view == View::SessionDetail {
         match code {
@@ -58,11 +107,7 @@ fn handle_normal_input(app: &mut App, code: KeyCode) {
                 return;
             }
             KeyCode::Char('j') | KeyCode::Down => {
-                let msg_count = app
-                    .current_project()
-                    .and_then(|p| p.sessions.get(app.selected_session))
-                    .map(|s| s.messages.len())
-                    .unwrap_or(0);
+                let msg_count = app.current_session().map(|s| s.messages.len()).unwrap_or(0);
                 app.scroll_messages_down(msg_count);
                 return;
             }
@@ -71,11 +116,7 @@ fn handle_normal_input(app: &mut App, code: KeyCode) {
                 return;
             }
             KeyCode::Char('d') => {
-                let msg_count = app
-                    .current_project()
-                    .and_then(|p| p.sessions.get(app.selected_session))
-                    .map(|s| s.messages.len())
-                    .unwrap_or(0);
+                let msg_count = app.current_session().map(|s| s.messages.len()).unwrap_or(0);
                 for _ in 0..10 {
                     app.scroll_messages_down(msg_count);
                 }
@@ -92,16 +133,42 @@ fn handle_normal_input(app: &mut App, code: KeyCode) {
                 return;
             }
             KeyCode::Char('G') => {
-                let msg_count = app
-                    .current_project()
-                    .and_then(|p| p.sessions.get(app.selected_session))
-                    .map(|s| s.messages.len())
-                    .unwrap_or(0);
+                let msg_count = app.current_session().map(|s| s.messages.len()).unwrap_or(0);
                 app.message_scroll = msg_count.saturating_sub(1);
                 return;
             }
+            KeyCode::Char('a') => {
+                app.cycle_agent();
+                return;
+            }
+            KeyCode::Char('z') => {
+                app.expand_thinking = !app.expand_thinking;
+                return;
+            }
+            KeyCode::Char('e') => {
+                app.toggle_message_tools();
+                return;
+            }
+            KeyCode::Char('x') => {
+                app.toggle_all_tools();
+                return;
+            }
+            KeyCode::Char('f') => {
+                app.open_find();
+                return;
+            }
+            KeyCode::Char('v') => {
+                app.open_message();
+                return;
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                app.open_selected_agent();
+                return;
+            }
             KeyCode::Esc | KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
-                app.go_back();
+                if !app.close_agent() {
+                    app.go_back();
+                }
                 return;
             }
             KeyCode::Char('t') => {
@@ -129,15 +196,25 @@ fn handle_normal_input(app: &mut App, code: KeyCode) {
         KeyCode::Esc | KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
             app.go_back();
         }
-        KeyCode::Char('/') => {
-            if app.view == View::ProjectList {
-                app.input_mode = InputMode::Search;
-                app.search_query.clear();
-            }
+        KeyCode::Char('/') if matches!(app.view, View::ProjectList | View::ProjectDetail) => {
+            app.input_mode = InputMode::Search;
+            app.filter_query().clear();
+        }
+        KeyCode::Char('f') => {
+            app.open_find();
         }
         KeyCode::Char('s') => {
             app.cycle_sort();
         }
+        KeyCode::Char('S') => {
+            app.reverse_sort();
+        }
+        KeyCode::Char('m') if app.view == View::ProjectDetail => {
+            app.cycle_model_filter();
+        }
+        KeyCode::Char('c') if app.view == View::ProjectDetail => {
+            app.cycle_source_filter();
+        }
         KeyCode::Char('t') => {
             app.theme = app.theme.next();
             save_theme(app.theme);
@@ -162,18 +239,17 @@ fn handle_normal_input(app: &mut App, code: KeyCode) {
             app.view = View::ProjectList;
             app.view_stack.clear();
         }
-        KeyCode::Char('3') => {
-            if app.current_project().is_some() {
-                app.view = View::ProjectDetail;
-                app.view_stack.clear();
-            }
+        KeyCode::Char('3') if app.current_project().is_some() => {
+            app.view = View::ProjectDetail;
+            app.view_stack.clear();
+            app.sync_sessions();
         }
         KeyCode::Char('4') => {
-            if let Some(proj) = app.current_project() {
-                if !proj.sessions.is_empty() {
-                    app.view = View::SessionDetail;
-                    app.view_stack.clear();
-                }
+            app.sync_sessions();
+            if app.selected_session_summary().is_some() {
+                app.view = View::SessionDetail;
+                app.view_stack.clear();
+                app.close_agents();
             }
         }
         _ => {}
@@ -2,20 +2,25 @@ use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{
         Block, Borders, Cell, Paragraph, Row, Scrollbar,
-        ScrollbarOrientation, ScrollbarState, Table, Widget,
+        ScrollbarOrientation, ScrollbarState, Table, Widget, Wrap,
     },
     Frame,
 };
 
 use crate::format::{
-    format_cost, format_duration, format_number, format_relative, short_model, truncate,
+    format_commits, format_cost, format_cost_breakdown, format_duration, format_number,
+    format_percent, format_relative, format_thinking, short_model, truncate,
 };
-use crate::models::DataSource;
+use std::collections::HashMap;
+
+use crate::markdown::{self, style_xml_content};
+use crate::models::{DataSource, ToolOutcomes};
+use crate::search::MAX_HITS;
 use crate::theme::ThemeColors;
-use crate::tui_app::{App, InputMode, SortColumn, View};
+use crate::tui_app::{App, InputMode, SessionSort, SortColumn, View};
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
     let tc = app.theme.colors();
@@ -47,6 +52,8 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         View::ProjectList => draw_project_list(frame, app, &tc, chunks[1]),
         View::ProjectDetail => draw_project_detail(frame, app, &tc, chunks[1]),
         View::SessionDetail => draw_session_detail(frame, app, &tc, chunks[1]),
+        View::Search => draw_search(frame, app, &tc, chunks[1]),
+        View::Message => draw_message_view(frame, app, &tc, chunks[1]),
     }
 
     draw_footer(frame, app, &tc, chunks[2]);
@@ -93,7 +100,7 @@ fn draw_loading(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
 
 fn draw_header(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
     let m = &app.metrics;
-    let header = Paragraph::new(Line::from(vec![
+    let mut spans = vec![
         Span::styled(" ct ", Style::default().fg(tc.accent).add_modifier(Modifier::BOLD)),
         Span::styled("│ ", Style::default().fg(tc.border)),
         Span::styled(
@@ -125,7 +132,20 @@ fn draw_header(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
             format!("Theme: {}", app.theme),
             Style::default().fg(tc.muted),
         ),
-    ]))
+    ];
+    if !app.window.is_unbounded() {
+        spans.push(Span::styled(" │ ", Style::default().fg(tc.border)));
+        spans.push(Span::styled(app.window.label(), Style::default().fg(tc.warning)));
+    }
+    if !m.unpriced_models.is_empty() {
+        spans.push(Span::styled(" │ ", Style::default().fg(tc.border)));
+        spans.push(Span::styled(
+            format!("No price: {}", m.unpriced_models.join(", ")),
+            Style::default().fg(tc.warning),
+        ));
+    }
+
+    let header = Paragraph::new(Line::from(spans))
     .block(
         Block::default()
             .borders(Borders::BOTTOM)
@@ -136,15 +156,30 @@ fn draw_header(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
 
 fn draw_footer(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
     let keys = match app.view {
-        View::Dashboard => "Enter: Projects │ t: Theme │ q: Quit",
+        View::Dashboard => "Enter: Projects │ f: Find │ t: Theme │ q: Quit",
         View::ProjectList => match app.input_mode {
             InputMode::Search => "Type to filter │ Enter: Confirm │ Esc: Cancel",
-            InputMode::Normal => "j/k: Navigate │ Enter: Detail │ /: Search │ s: Sort │ t: Theme │ q: Quit",
+            InputMode::Normal => "j/k: Navigate │ Enter: Detail │ /: Search │ f: Find │ s/S: Sort/Reverse │ t: Theme │ q: Quit",
+        },
+        View::ProjectDetail => match app.input_mode {
+            InputMode::Search => "Type to filter │ Enter: Confirm │ Esc: Cancel",
+            InputMode::Normal => {
+                "j/k: Navigate │ Enter: Session │ /: Filter │ m: Model │ c: Source │ s/S: Sort/Reverse │ f: Find │ Esc: Back │ t: Theme │ q: Quit"
+            }
         },
-        View::ProjectDetail => "j/k: Navigate │ Enter: Session │ Esc: Back │ t: Theme │ q: Quit",
+        View::SessionDetail if app.open_agent.is_some() => {
+            "j/k: Scroll │ u/d: Page │ g/G: Top/Bottom │ v: View message │ z: Thinking │ e/x: Tools │ f: Find │ Esc: Parent session │ t: Theme │ q: Quit"
+        }
+        View::SessionDetail
+            if app.selected_session_summary().is_some_and(|s| !s.agents.is_empty()) =>
+        {
+            "j/k: Scroll │ u/d: Page │ g/G: Top/Bottom │ v: View message │ z: Thinking │ e/x: Tools │ a: Next agent │ Enter: Open agent │ f: Find │ Esc: Back │ t: Theme │ q: Quit"
+        }
         View::SessionDetail => {
-            "j/k: Scroll │ u/d: Page │ g/G: Top/Bottom │ Esc: Back │ t: Theme │ q: Quit"
+            "j/k: Scroll │ u/d: Page │ g/G: Top/Bottom │ v: View message │ z: Thinking │ e/x: Tools │ f: Find │ Esc: Back │ t: Theme │ q: Quit"
         }
+        View::Search => "Type to search │ ↑/↓: Select │ Enter: Open message │ Esc: Back",
+        View::Message => "j/k: Scroll │ u/d: Page │ g/G: Top/Bottom │ Esc: Back to thread │ t: Theme │ q: Quit",
     };
 
     let footer = Paragraph::new(Span::styled(
@@ -236,7 +271,7 @@ impl<'a> Widget for StackedBarChart<'a> {
             (data, labels)
         } else {
             // Aggregate entries into buckets
-            let bucket_size = (total_entries + available_cols - 1) / available_cols;
+            let bucket_size = total_entries.div_ceil(available_cols);
             let mut data = Vec::new();
             let mut labels = Vec::new();
             for chunk in self.timeline.chunks(bucket_size) {
@@ -269,7 +304,7 @@ impl<'a> Widget for StackedBarChart<'a> {
         }
 
         // Draw bars
-        let cols_per_bar = if num_bars > 0 { available_cols / num_bars } else { 1 };
+        let cols_per_bar = available_cols.checked_div(num_bars).unwrap_or(1);
         let cols_per_bar = cols_per_bar.max(1);
 
         for (i, &(claude, cursor)) in bar_data.iter().enumerate() {
@@ -288,7 +323,7 @@ impl<'a> Widget for StackedBarChart<'a> {
             let cursor_height = bar_height.saturating_sub(claude_height);
 
             let x_start = bar_area_x + (i * cols_per_bar) as u16;
-            let bar_width = if cols_per_bar > 1 { (cols_per_bar - 0) as u16 } else { 1 };
+            let bar_width = if cols_per_bar > 1 { cols_per_bar as u16 } else { 1 };
 
             // Draw from bottom up: Claude first (bottom), then Cursor (top)
             for dy in 0..bar_height {
@@ -400,23 +435,32 @@ fn draw_dashboard(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
     // Token breakdown + tool usage
     let mid_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .constraints([
+            Constraint::Percentage(34),
+            Constraint::Percentage(33),
+            Constraint::Percentage(33),
+        ])
         .split(chunks[1]);
 
     // Token breakdown with proportional bars
     let token_values = [
-        ("Input", m.total_tokens.input, tc.token_input),
-        ("Output", m.total_tokens.output, tc.token_output),
-        ("Cache R", m.total_tokens.cache_read, tc.token_cache),
-        ("Cache W", m.total_tokens.cache_creation, tc.token_cache),
+        ("Input", m.total_tokens.input, m.cost_parts.input, tc.token_input),
+        ("Output", m.total_tokens.output, m.cost_parts.output, tc.token_output),
+        ("Cache R", m.total_tokens.cache_read, m.cost_parts.cache_read, tc.token_cache),
+        ("Cache W", m.total_tokens.cache_creation, m.cost_parts.cache_write, tc.token_cache),
     ];
-    let token_max = token_values.iter().map(|(_, v, _)| *v).max().unwrap_or(1);
-    let bar_width = mid_chunks[0].width.saturating_sub(22); // label + value space
+    let token_max = token_values.iter().map(|(_, v, _, _)| *v).max().unwrap_or(1);
+    let bar_width = mid_chunks[0].width.saturating_sub(30); // label + value + cost space
 
     let mut token_lines: Vec<Line> = token_values
         .iter()
-        .map(|(label, val, color)| {
-            unicode_bar_line(label, *val, token_max, bar_width, 8, *color, tc)
+        .map(|(label, val, cost, color)| {
+            let mut line = unicode_bar_line(label, *val, token_max, bar_width, 8, *color, tc);
+            line.push_span(Span::styled(
+                format!("  {}", format_cost(*cost)),
+                Style::default().fg(tc.success),
+            ));
+            line
         })
         .collect();
 
@@ -440,30 +484,8 @@ fn draw_dashboard(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
     );
     frame.render_widget(token_block, mid_chunks[0]);
 
-    // Tool usage — unicode horizontal bars
-    let mut sorted_tools: Vec<(&String, &u64)> = m.tool_usage.iter().collect();
-    sorted_tools.sort_by(|a, b| b.1.cmp(a.1));
-    sorted_tools.truncate(8);
-
-    let tool_max = sorted_tools.first().map(|(_, &v)| v).unwrap_or(1);
-    let tool_bar_width = mid_chunks[1].width.saturating_sub(22);
-
-    let tool_lines: Vec<Line> = sorted_tools
-        .iter()
-        .enumerate()
-        .map(|(i, (name, &count))| {
-            let color = if i % 2 == 0 { tc.bar } else { tc.bar_alt };
-            unicode_bar_line(&truncate(name, 10), count, tool_max, tool_bar_width, 10, color, tc)
-        })
-        .collect();
-
-    let tool_block = Paragraph::new(tool_lines).block(
-        Block::default()
-            .title(Span::styled(" Tool Usage ", Style::default().fg(tc.title)))
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(tc.border)),
-    );
-    frame.render_widget(tool_block, mid_chunks[1]);
+    draw_call_bars(frame, tc, mid_chunks[1], " Tool Usage ", &m.tool_usage, &m.tool_outcomes, 10);
+    draw_call_bars(frame, tc, mid_chunks[2], " Bash Commands ", &m.command_usage, &m.command_outcomes, 16);
 
     // Activity stacked bar chart
     let peak_info = app.metrics.timeline.iter()
@@ -507,6 +529,50 @@ fn draw_dashboard(frame: &mut Frame, app: &App, tc: &ThemeColors, area: Rect) {
     frame.render_widget(chart, chunks[2]);
 }
 
+/// Top calls by count as unicode horizontal bars, with failure rates where any failed
+fn draw_call_bars(
+    frame: &mut Frame,
+    tc: &ThemeColors,
+    area: Rect,
+    title: &str,
+    usage: &HashMap<String, u64>,
+    outcomes: &HashMap<String, ToolOutcomes>,
+    label_width: usize,
+) {
+    let mut sorted: Vec<(&String, &u64)> = usage.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    sorted.truncate(8);
+
+    let max = sorted.first().map(|(_, &v)| v).unwrap_or(1);
+    let bar_width = area.width.saturating_sub(label_width as u16 + 25); // label + count + failure space
+
+    let lines: Vec<Line> = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, (name, &count))| {
+            let color = if i % 2 == 0 { tc.bar } else { tc.bar_alt };
+            let mut line =
+                unicode_bar_line(&truncate(name, label_width - 3), count, max, bar_width, label_width, color, tc);
+            let failure_rate = outcomes.get(*name).and_then(|o| o.failure_rate());
+            if let Some(rate) = failure_rate.filter(|r| *r > 0.0) {
+                line.push_span(Span::styled(
+                    format!(" ({} failed)", format_percent(rate)),
+                    Style::default().fg(tc.danger),
+                ));
+            }
+            line
+        })
+        .collect();
+
+    let block = Paragraph::new(lines).block(
+        Block::default()
+            .title(Span::styled(title.to_string(), Style::default().fg(tc.title)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(tc.border)),
+    );
+    frame.render_widget(block, area);
+}
+
 fn draw_stat_card(frame: &mut Frame, tc: &ThemeColors, area: Rect, label: &str, value: &str, color: Color) {
     let card = Paragraph::new(vec![
         Line::from(""),
@@ -582,7 +648,11 @@ fn draw_project_list(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area: R
 
     // Build sort indicator
     let sort_indicator = |col: SortColumn| -> &str {
-        if app.sort_column == col { " ▼" } else { "" }
+        match (app.sort_column == col, app.sort_ascending) {
+            (false, _) => "",
+            (true, true) => " ▲",
+            (true, false) => " ▼",
+        }
     };
 
     // Project table
@@ -702,7 +772,8 @@ fn draw_project_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(5), // project info
+            Constraint::Length(8), // project info
+            Constraint::Length(1), // sort and filter
             Constraint::Min(0),   // session table
         ])
         .split(area);
@@ -735,6 +806,27 @@ fn draw_project_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
                 Style::default().fg(tc.fg),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("Thinking: ", Style::default().fg(tc.muted)),
+            Span::styled(
+                format_thinking(project.thinking_turns, project.thinking_chars),
+                Style::default().fg(tc.fg),
+            ),
+            Span::styled("  │  ", Style::default().fg(tc.border)),
+            Span::styled("Commits: ", Style::default().fg(tc.muted)),
+            Span::styled(
+                format_commits(project.commit_count, project.cost_per_commit()),
+                Style::default().fg(tc.fg),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Cost by model: ", Style::default().fg(tc.muted)),
+            Span::styled(
+                format_cost_breakdown(&project.cost_by_model),
+                Style::default().fg(tc.success),
+            ),
+        ]),
+        thread_line(&project, app.selected_session_summary(), tc),
     ])
     .block(
         Block::default()
@@ -743,23 +835,84 @@ fn draw_project_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
     );
     frame.render_widget(info, chunks[0]);
 
+    // Sort and filter bar
+    let filter = &app.session_filter;
+    let status = if app.input_mode == InputMode::Search {
+        Line::from(vec![
+            Span::styled(" / ", Style::default().fg(tc.accent)),
+            Span::styled(filter.text.clone(), Style::default().fg(tc.fg)),
+            Span::styled("█", Style::default().fg(tc.accent)),
+        ])
+    } else {
+        let mut spans = vec![Span::styled(
+            format!(
+                " Sort: {} {} ",
+                app.session_sort.label(),
+                if app.session_sort_ascending { "▲" } else { "▼" }
+            ),
+            Style::default().fg(tc.muted),
+        )];
+        if !filter.text.is_empty() {
+            spans.push(Span::styled(format!("│ Filter: {} ", filter.text), Style::default().fg(tc.accent)));
+        }
+        if let Some(model) = &filter.model {
+            spans.push(Span::styled(format!("│ Model: {} ", short_model(model)), Style::default().fg(tc.accent)));
+        }
+        if let Some(source) = filter.source {
+            spans.push(Span::styled(format!("│ Source: {} ", source), Style::default().fg(tc.accent)));
+        }
+        spans.push(Span::styled(
+            format!("│ {}/{} sessions", app.filtered_sessions.len(), project.sessions.len()),
+            Style::default().fg(tc.muted),
+        ));
+        Line::from(spans)
+    };
+    frame.render_widget(Paragraph::new(status), chunks[1]);
+
     // Session table
-    let header = Row::new(vec![
-        Cell::from("First Prompt").style(Style::default().fg(tc.accent)),
-        Cell::from("Messages").style(Style::default().fg(tc.accent)),
-        Cell::from("Tokens").style(Style::default().fg(tc.accent)),
-        Cell::from("Duration").style(Style::default().fg(tc.accent)),
-        Cell::from("Lines +/-").style(Style::default().fg(tc.accent)),
-        Cell::from("Model").style(Style::default().fg(tc.accent)),
-        Cell::from("Started").style(Style::default().fg(tc.accent)),
-    ]);
+    let sort_indicator = |col: SessionSort| -> &str {
+        match (app.session_sort == col, app.session_sort_ascending) {
+            (false, _) => "",
+            (true, true) => " ▲",
+            (true, false) => " ▼",
+        }
+    };
+    let header_cells = [
+        String::new(),
+        "First Prompt".to_string(),
+        format!("Messages{}", sort_indicator(SessionSort::Messages)),
+        format!("Tokens{}", sort_indicator(SessionSort::Tokens)),
+        format!("Duration{}", sort_indicator(SessionSort::Duration)),
+        format!("Lines +/-{}", sort_indicator(SessionSort::Lines)),
+        "Commits".to_string(),
+        format!("Cost{}", sort_indicator(SessionSort::Cost)),
+        format!("Model{}", sort_indicator(SessionSort::Model)),
+        format!("Started{}", sort_indicator(SessionSort::Started)),
+    ];
+    let header = Row::new(
+        header_cells
+            .iter()
+            .map(|h| Cell::from(h.as_str()).style(Style::default().fg(tc.accent))),
+    );
 
-    let rows: Vec<Row> = project
-        .sessions
+    // Thread glyphs only make sense while a thread's sessions are adjacent, oldest first
+    let show_threads = app.session_sort == SessionSort::Thread
+        && app.session_sort_ascending
+        && !filter.is_active();
+    let rows: Vec<Row> = app
+        .filtered_sessions
         .iter()
-        .map(|s| {
+        .map(|&idx| {
+            let s = &project.sessions[idx];
             let mc = tc.model_color(&s.model);
+            let glyph = match project.thread_of(s).filter(|_| show_threads) {
+                Some((0, _)) => "┌",
+                Some((pos, t)) if pos + 1 == t.session_ids.len() => "└",
+                Some(_) => "├",
+                None => " ",
+            };
             Row::new(vec![
+                Cell::from(glyph).style(Style::default().fg(tc.accent)),
                 Cell::from(Line::from(style_xml_content(&s.first_prompt, tc.fg, tc.xml_tag))),
                 Cell::from(s.messages.len().to_string()).style(Style::default().fg(tc.fg)),
                 Cell::from(format_number(s.total_tokens.total()))
@@ -771,6 +924,9 @@ fn draw_project_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
                     format_number(s.lines_removed)
                 ))
                 .style(Style::default().fg(tc.success)),
+                Cell::from(if s.commits.is_empty() { String::new() } else { s.commits.len().to_string() })
+                    .style(Style::default().fg(tc.fg)),
+                Cell::from(format_cost(s.cost)).style(Style::default().fg(tc.success)),
                 Cell::from(short_model(&s.model)).style(Style::default().fg(mc)),
                 Cell::from(format_relative(&s.started_at)).style(Style::default().fg(tc.muted)),
             ])
@@ -780,11 +936,14 @@ fn draw_project_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
     let table = Table::new(
         rows,
         [
+            Constraint::Length(1),
             Constraint::Min(30),
             Constraint::Length(8),
             Constraint::Length(10),
             Constraint::Length(10),
             Constraint::Length(14),
+            Constraint::Length(8),
+            Constraint::Length(9),
             Constraint::Length(12),
             Constraint::Length(12),
         ],
@@ -803,17 +962,48 @@ fn draw_project_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
             .add_modifier(Modifier::BOLD),
     );
 
-    frame.render_stateful_widget(table, chunks[1], &mut app.session_table_state);
+    frame.render_stateful_widget(table, chunks[2], &mut app.session_table_state);
 }
 
-fn draw_session_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area: Rect) {
-    let project = match app.current_project() {
-        Some(p) => p.clone(),
-        None => return,
+/// Combined totals of the selected session's thread, if it has one
+fn thread_line<'a>(
+    project: &crate::models::ProjectSummary,
+    selected: Option<&crate::models::ParsedSession>,
+    tc: &ThemeColors,
+) -> Line<'a> {
+    let Some((pos, thread)) = selected.and_then(|s| project.thread_of(s)) else {
+        return Line::from("");
     };
-    let session = match project.sessions.get(app.selected_session) {
-        Some(s) => s.clone(),
-        None => return,
+    let mut spans = vec![
+        Span::styled(
+            format!("Thread {}/{}: ", pos + 1, thread.session_ids.len()),
+            Style::default().fg(tc.muted),
+        ),
+        Span::styled(
+            format!(
+                "{} messages │ {} tokens │ +{}/−{} lines │ {}",
+                thread.message_count,
+                format_number(thread.total_tokens.total()),
+                format_number(thread.lines_added),
+                format_number(thread.lines_removed),
+                format_cost(thread.cost),
+            ),
+            Style::default().fg(tc.fg),
+        ),
+    ];
+    if !thread.summary.is_empty() {
+        spans.push(Span::styled(
+            format!("  {}", thread.summary),
+            Style::default().fg(tc.muted),
+        ));
+    }
+    Line::from(spans)
+}
+
+fn draw_session_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area: Rect) {
+    let (parent, session) = match (app.selected_session_summary(), app.current_session()) {
+        (Some(p), Some(s)) => (p.clone(), s.clone()),
+        _ => return,
     };
 
     // Layout: compact info at top, then messages + files side by side
@@ -835,7 +1025,73 @@ fn draw_session_detail(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area:
         .split(chunks[1]);
 
     draw_message_thread(frame, app, &session, tc, bottom_chunks[0]);
-    draw_files_panel(frame, &session, tc, bottom_chunks[1]);
+    if parent.agents.is_empty() {
+        draw_files_panel(frame, &session, tc, bottom_chunks[1]);
+    } else {
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(bottom_chunks[1]);
+        draw_files_panel(frame, &session, tc, side[0]);
+        draw_agents_panel(frame, app, &parent, tc, side[1]);
+    }
+}
+
+/// Subagent threads of a session; the highlighted one opens with Enter
+fn draw_agents_panel(
+    frame: &mut Frame,
+    app: &App,
+    parent: &crate::models::ParsedSession,
+    tc: &ThemeColors,
+    area: Rect,
+) {
+    let rows: Vec<Row> = parent
+        .agents
+        .iter()
+        .enumerate()
+        .map(|(i, agent)| {
+            let marker = if app.open_agent == Some(i) { "▶" } else { " " };
+            let row = Row::new(vec![
+                Cell::from(marker).style(Style::default().fg(tc.accent)),
+                Cell::from(agent.first_prompt.clone()).style(Style::default().fg(tc.fg)),
+                Cell::from(agent.messages.len().to_string()).style(Style::default().fg(tc.muted)),
+                Cell::from(format_cost(agent.cost)).style(Style::default().fg(tc.success)),
+            ]);
+            if i == app.selected_agent {
+                row.style(Style::default().bg(tc.highlight_bg).fg(tc.highlight_fg))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from(""),
+        Cell::from("Task").style(Style::default().fg(tc.accent)),
+        Cell::from("Msgs").style(Style::default().fg(tc.accent)),
+        Cell::from("Cost").style(Style::default().fg(tc.accent)),
+    ]);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Min(10),
+            Constraint::Length(5),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(Span::styled(
+                format!(" Agents ({} · {}) ", parent.agents.len(), format_cost(parent.agents_cost())),
+                Style::default().fg(tc.title),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(tc.border)),
+    );
+    frame.render_widget(table, area);
 }
 
 fn draw_session_info_compact(
@@ -845,18 +1101,40 @@ fn draw_session_info_compact(
     area: Rect,
 ) {
     let model_color = tc.model_color(&session.model);
-    let cost = crate::format::estimate_cost(
-        &session.model,
-        session.total_tokens.input,
-        session.total_tokens.output,
-        session.total_tokens.cache_read,
-    );
+    let cost = session.cost;
     let source_color = match session.source {
         DataSource::Cursor => tc.cursor_badge,
         DataSource::Claude => tc.claude_badge,
     };
 
-    let prompt_spans = style_xml_content(&session.first_prompt, tc.fg, tc.xml_tag);
+    let mut prompt_spans = Vec::new();
+    if session.parent_session.is_some() {
+        prompt_spans.push(Span::styled(
+            format!("↳ {}  ", session.session_id),
+            Style::default().fg(tc.accent).add_modifier(Modifier::BOLD),
+        ));
+    }
+    prompt_spans.extend(style_xml_content(&session.first_prompt, tc.fg, tc.xml_tag));
+
+    let mut commit_spans = Vec::new();
+    if !session.commits.is_empty() {
+        commit_spans.push(Span::styled("Commits: ", Style::default().fg(tc.muted)));
+        commit_spans.push(Span::styled(
+            format_commits(session.commits.len(), session.cost_per_commit()),
+            Style::default().fg(tc.fg),
+        ));
+        for commit in &session.commits {
+            commit_spans.push(Span::styled("  │  ", Style::default().fg(tc.border)));
+            commit_spans.push(Span::styled(
+                commit.hash.chars().take(8).collect::<String>(),
+                Style::default().fg(tc.accent),
+            ));
+            commit_spans.push(Span::styled(
+                format!(" {}", commit.subject),
+                Style::default().fg(tc.muted),
+            ));
+        }
+    }
     let info = Paragraph::new(vec![
         Line::from(prompt_spans),
         Line::from(vec![
@@ -868,6 +1146,8 @@ fn draw_session_info_compact(
             Span::styled("  │  ", Style::default().fg(tc.border)),
             Span::styled(format_duration(session.duration_ms), Style::default().fg(tc.fg)),
             Span::styled("  │  ", Style::default().fg(tc.border)),
+            // Some edits' inputs don't show their whole change
+            Span::styled(if session.partial_edits > 0 { "≥" } else { "" }, Style::default().fg(tc.muted)),
             Span::styled(format!("+{}", format_number(session.lines_added)), Style::default().fg(tc.success)),
             Span::styled("/", Style::default().fg(tc.muted)),
             Span::styled(format!("−{}", format_number(session.lines_removed)), Style::default().fg(tc.danger)),
@@ -876,14 +1156,16 @@ fn draw_session_info_compact(
             Span::styled("  │  ", Style::default().fg(tc.border)),
             Span::styled(
                 format!(
-                    "{}in/{}out/{}cache",
+                    "{}in/{}out/{}cache R/{}cache W",
                     format_number(session.total_tokens.input),
                     format_number(session.total_tokens.output),
                     format_number(session.total_tokens.cache_read),
+                    format_number(session.total_tokens.cache_creation),
                 ),
                 Style::default().fg(tc.muted),
             ),
         ]),
+        Line::from(commit_spans),
     ])
     .block(
         Block::default()
@@ -901,7 +1183,7 @@ fn draw_files_panel(
 ) {
     let mut sorted_files: Vec<(&String, &crate::models::FileContribution)> =
         session.file_contributions.iter().collect();
-    sorted_files.sort_by(|a, b| (b.1.added + b.1.removed).cmp(&(a.1.added + a.1.removed)));
+    sorted_files.sort_by_key(|b| std::cmp::Reverse(b.1.added + b.1.removed));
 
     let file_rows: Vec<Row> = sorted_files
         .iter()
@@ -944,71 +1226,257 @@ fn draw_files_panel(
     frame.render_widget(file_table, area);
 }
 
-/// Remove XML tag markup but keep the content between tags, colored in `tag_color`.
-/// `<command-message>hello</command-message> world` →
-///   [Span("hello", purple), Span(" world", normal)]
-fn style_xml_content<'a>(s: &str, text_color: Color, tag_color: Color) -> Vec<Span<'a>> {
-    let mut spans: Vec<Span<'a>> = Vec::new();
-    let mut current_text = String::new();
-    let mut in_tag_content = false; // true when we're between <tag> and </tag>
-    let mut chars = s.chars().peekable();
-
-    while let Some(ch) = chars.next() {
-        if ch == '<' {
-            // Scan ahead for closing '>'
-            let mut tag = String::from('<');
-            let mut found_close = false;
-            if chars.peek().map_or(true, |c| c.is_whitespace()) {
-                current_text.push(ch);
-                continue;
+fn draw_search(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(4)])
+        .split(area);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("Find: ", Style::default().fg(tc.muted)),
+        Span::styled(app.find_query.clone(), Style::default().fg(tc.fg).add_modifier(Modifier::BOLD)),
+        Span::styled("▌", Style::default().fg(tc.accent)),
+    ]))
+    .block(
+        Block::default()
+            .title(Span::styled(" Search conversations ", Style::default().fg(tc.title)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(tc.border)),
+    );
+    frame.render_widget(input, chunks[0]);
+
+    let mark = Style::default()
+        .fg(tc.highlight_fg)
+        .bg(tc.warning)
+        .add_modifier(Modifier::BOLD);
+    let rows: Vec<Row> = app
+        .find_hits
+        .iter()
+        .map(|hit| {
+            let project = &app.projects[hit.project];
+            let parent = &project.sessions[hit.session];
+            let session = hit.agent.map_or(parent, |a| &parent.agents[a]);
+
+            let mut title = vec![
+                Span::styled(project.name.clone(), Style::default().fg(tc.accent).add_modifier(Modifier::BOLD)),
+                Span::styled(" › ", Style::default().fg(tc.border)),
+                Span::styled(truncate(&parent.first_prompt, 60), Style::default().fg(tc.fg)),
+            ];
+            if hit.agent.is_some() {
+                title.push(Span::styled(
+                    format!(" › ↳ {}", truncate(&session.first_prompt, 40)),
+                    Style::default().fg(tc.fg),
+                ));
             }
-            for next in chars.by_ref() {
-                tag.push(next);
-                if next == '>' {
-                    found_close = true;
-                    break;
-                }
-                if tag.len() > 200 {
-                    break;
-                }
+            title.push(Span::styled(
+                format!("  {} · {}", hit.field.label(), format_relative(&hit.timestamp)),
+                Style::default().fg(tc.muted),
+            ));
+
+            let mut snippet = vec![Span::raw("  ")];
+            let mut pos = 0;
+            for range in &hit.highlights {
+                snippet.push(Span::styled(hit.snippet[pos..range.start].to_string(), Style::default().fg(tc.muted)));
+                snippet.push(Span::styled(hit.snippet[range.clone()].to_string(), mark));
+                pos = range.end;
             }
-            if found_close {
-                // Flush accumulated text before this tag
-                if !current_text.is_empty() {
-                    let color = if in_tag_content { tag_color } else { text_color };
-                    spans.push(Span::styled(current_text.clone(), Style::default().fg(color)));
-                    current_text.clear();
-                }
-                // Check if this is an opening or closing tag
-                let is_closing = tag.starts_with("</");
-                if is_closing {
-                    in_tag_content = false;
-                } else {
-                    in_tag_content = true;
-                }
-                // Tag markup itself is not rendered
-            } else {
-                current_text.push_str(&tag);
+            snippet.push(Span::styled(hit.snippet[pos..].to_string(), Style::default().fg(tc.muted)));
+
+            Row::new(vec![Cell::from(Text::from(vec![Line::from(title), Line::from(snippet)]))])
+                .height(2)
+                .bottom_margin(1)
+        })
+        .collect();
+
+    let title = if app.find_query.trim().is_empty() {
+        " Results ".to_string()
+    } else if app.find_hits.len() >= MAX_HITS {
+        format!(" Results (top {}) ", MAX_HITS)
+    } else {
+        format!(" Results ({}) ", app.find_hits.len())
+    };
+    let table = Table::new(rows, [Constraint::Min(10)])
+        .block(
+            Block::default()
+                .title(Span::styled(title, Style::default().fg(tc.title)))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(tc.border)),
+        )
+        .row_highlight_style(Style::default().bg(tc.highlight_bg));
+    frame.render_stateful_widget(table, chunks[1], &mut app.find_table_state);
+}
+
+/// A tool call as one row — name, key argument and outcome — followed, when expanded,
+/// by its input and output
+fn tool_call_lines<'a>(
+    tool: &crate::models::ToolUse,
+    expanded: bool,
+    max_lines: usize,
+    tc: &ThemeColors,
+) -> Vec<Line<'a>> {
+    let muted = Style::default().fg(tc.muted);
+    let mut row = vec![
+        Span::styled(if expanded { "  ▾ " } else { "  ▸ " }, muted),
+        Span::styled(
+            format!("⚙ {}", tool.name),
+            Style::default().fg(tc.accent).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!("  {}", truncate(&tool.summary, 100)), Style::default().fg(tc.fg)),
+    ];
+    match tool.result {
+        None => row.push(Span::styled("  …", muted)),
+        Some(ref r) if r.is_error => row.push(Span::styled("  ✗ error", Style::default().fg(tc.danger))),
+        Some(ref r) => {
+            row.push(Span::styled("  ✓", Style::default().fg(tc.success)));
+            if !r.output.is_empty() {
+                row.push(Span::styled(format!(" {} lines", r.output.lines().count()), muted));
             }
-        } else {
-            current_text.push(ch);
         }
     }
+    let mut lines = vec![Line::from(row)];
+    if !expanded {
+        return lines;
+    }
 
-    // Flush remaining text
-    if !current_text.is_empty() {
-        let color = if in_tag_content { tag_color } else { text_color };
-        spans.push(Span::styled(current_text, Style::default().fg(color)));
+    // The key argument is already on the row
+    let input: Vec<&str> = tool
+        .input
+        .lines()
+        .filter(|l| l.trim() != tool.summary)
+        .collect();
+    let mut push_capped = |text: Vec<&str>, prefix: &str, style: Style| {
+        for line in text.iter().take(max_lines) {
+            lines.push(Line::from(Span::styled(format!("{}{}", prefix, truncate(line, 200)), style)));
+        }
+        if text.len() > max_lines {
+            lines.push(Line::from(Span::styled(
+                format!("{}... ({} more lines)", prefix, text.len() - max_lines),
+                muted,
+            )));
+        }
+    };
+    push_capped(input, "      ", muted);
+    if let Some(ref r) = tool.result {
+        let style = Style::default().fg(if r.is_error { tc.danger } else { tc.fg });
+        push_capped(r.output.lines().collect(), "    │ ", style);
     }
+    lines
+}
+
+/// One message in full: thinking, text and tool calls, wrapped and scrollable
+fn draw_message_view(frame: &mut Frame, app: &mut App, tc: &ThemeColors, area: Rect) {
+    let theme = app.theme;
+    let Some(view) = app.message_view.as_mut() else {
+        return;
+    };
+    let msg = &view.message;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(2), Constraint::Min(3)])
+        .split(area);
 
-    if spans.is_empty() {
-        spans.push(Span::styled("", Style::default()));
+    let (role_label, role_color) = if msg.role == "user" {
+        ("You", tc.accent)
+    } else {
+        ("Assistant", tc.token_output)
+    };
+    let mut title = vec![
+        Span::styled(role_label, Style::default().fg(role_color).add_modifier(Modifier::BOLD)),
+        Span::styled(
+            format!("  {} ({})", msg.timestamp, format_relative(&msg.timestamp)),
+            Style::default().fg(tc.muted),
+        ),
+    ];
+    if let Some(ref model) = msg.model {
+        title.push(Span::styled(
+            format!("  {}", short_model(model)),
+            Style::default().fg(tc.model_color(model)),
+        ));
+    }
+    if msg.cost > 0.0 {
+        title.push(Span::styled(format!("  {}", format_cost(msg.cost)), Style::default().fg(tc.success)));
+    }
+    let status = match view.error {
+        Some(ref e) => Span::styled(
+            format!("Showing the stored preview: {}", e),
+            Style::default().fg(tc.warning),
+        ),
+        None => Span::styled(
+            format!("{} chars", format_number(msg.content.chars().count() as u64)),
+            Style::default().fg(tc.muted),
+        ),
+    };
+    frame.render_widget(Paragraph::new(vec![Line::from(title), Line::from(status)]), chunks[0]);
+
+    let mut lines: Vec<Line> = Vec::new();
+    if !msg.thinking.is_empty() {
+        let dim = Style::default()
+            .fg(tc.muted)
+            .add_modifier(Modifier::DIM | Modifier::ITALIC);
+        lines.push(Line::from(Span::styled("Thinking", dim.add_modifier(Modifier::BOLD))));
+        lines.extend(msg.thinking.lines().map(|l| Line::from(Span::styled(l.to_string(), dim))));
+        lines.push(Line::from(""));
+    }
+    if msg.content.is_empty() {
+        lines.push(Line::from(Span::styled("(no text content)", Style::default().fg(tc.muted))));
+    } else if msg.role == "user" {
+        lines.extend(msg.content.lines().map(|l| Line::from(style_xml_content(l, tc.fg, tc.xml_tag))));
+    } else {
+        if view.rendered.as_ref().is_none_or(|(t, _)| *t != theme) {
+            view.rendered = Some((theme, markdown::render(&msg.content, tc)));
+        }
+        if let Some((_, ref rendered)) = view.rendered {
+            lines.extend(rendered.iter().cloned());
+        }
+    }
+    for tool in &msg.tool_uses {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("⚙ {}", tool.name),
+            Style::default().fg(tc.accent).add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(
+            tool.input
+                .lines()
+                .map(|l| Line::from(Span::styled(format!("  {}", l), Style::default().fg(tc.muted)))),
+        );
+        if let Some(ref r) = tool.result {
+            let style = Style::default().fg(if r.is_error { tc.danger } else { tc.fg });
+            lines.extend(r.output.lines().map(|l| Line::from(Span::styled(format!("  │ {}", l), style))));
+        }
     }
 
-    spans
-}
+    // Estimate wrapped height to keep the scroll within the text
+    let inner_width = chunks[1].width.saturating_sub(3).max(1) as usize;
+    let inner_height = chunks[1].height.saturating_sub(2) as usize;
+    let wrapped: usize = lines.iter().map(|l| l.width().div_ceil(inner_width).max(1)).sum();
+    view.scroll = view.scroll.min(wrapped.saturating_sub(inner_height));
 
+    let body = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((view.scroll.min(u16::MAX as usize) as u16, 0))
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    format!(" Message {}/{} ", view.index + 1, view.total),
+                    Style::default().fg(tc.title),
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(tc.border)),
+        );
+    frame.render_widget(body, chunks[1]);
 
+    if wrapped > inner_height {
+        let mut scrollbar_state =
+            ScrollbarState::new(wrapped.saturating_sub(inner_height)).position(view.scroll);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .style(Style::default().fg(tc.muted));
+        frame.render_stateful_widget(scrollbar, chunks[1], &mut scrollbar_state);
+    }
+}
 
 fn draw_message_thread(
     frame: &mut Frame,
@@ -1033,80 +1501,37 @@ fn draw_message_thread(
 
     // Available height inside the block (borders take 2 lines)
     let inner_height = area.height.saturating_sub(2) as usize;
-    let max_lines_per_msg: usize = 8;
-
-    // Build display lines for all messages
-    let mut all_lines: Vec<Line> = Vec::new();
-    // Track which line index each message starts at for scrolling
-    let mut msg_line_offsets: Vec<usize> = Vec::new();
-
-    for msg in messages {
-        msg_line_offsets.push(all_lines.len());
-
-        let (role_label, role_color) = if msg.role == "user" {
-            ("You", tc.accent)
-        } else {
-            ("Assistant", tc.token_output)
-        };
 
-        let ts = format_relative(&msg.timestamp);
+    // Message opened from a search hit
+    let focused = app
+        .focused_message
+        .as_ref()
+        .filter(|(id, _)| *id == session.session_id)
+        .map(|(_, i)| *i);
 
-        // Role header
-        all_lines.push(Line::from(vec![
-            Span::styled(
-                format!("── {} ", role_label),
-                Style::default().fg(role_color).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(ts, Style::default().fg(tc.muted)),
-            Span::styled(
-                " ──────────────────────────────────────────",
-                Style::default().fg(tc.border),
-            ),
-        ]));
-
-        // Content lines
-        let content = &msg.content;
-        if content.is_empty() {
-            all_lines.push(Line::from(Span::styled(
-                "  (no text content)",
-                Style::default().fg(tc.muted),
-            )));
-        } else {
-            let content_lines: Vec<&str> = content.lines().collect();
-            let total = content_lines.len();
-            let show = total.min(max_lines_per_msg);
-            for line in content_lines.iter().take(show) {
-                let display = if line.len() > 200 {
-                    format!("  {}...", &line[..197])
-                } else {
-                    format!("  {}", line)
-                };
-                let spans = style_xml_content(&display, tc.fg, tc.xml_tag);
-                all_lines.push(Line::from(spans));
-            }
-            if total > max_lines_per_msg {
-                all_lines.push(Line::from(Span::styled(
-                    format!("  ... ({} more lines)", total - max_lines_per_msg),
-                    Style::default().fg(tc.muted),
-                )));
-            }
+    // Only build what can be seen: from the top message down until the panel is full,
+    // then, near the end of the thread, earlier messages to fill it from the bottom up
+    let top = app.message_scroll.min(messages.len() - 1);
+    let mut all_lines: Vec<Line> = Vec::new();
+    for (i, msg) in messages.iter().enumerate().skip(top) {
+        all_lines.extend(message_lines(app, msg, focused == Some(i), tc));
+        if all_lines.len() >= inner_height {
+            break;
         }
-
-        // Blank separator line
-        all_lines.push(Line::from(""));
     }
-
-    // Calculate scroll offset in lines based on message_scroll
-    let scroll_line = if app.message_scroll < msg_line_offsets.len() {
-        msg_line_offsets[app.message_scroll]
+    let mut first = top;
+    while all_lines.len() < inner_height && first > 0 {
+        first -= 1;
+        let mut earlier = message_lines(app, &messages[first], focused == Some(first), tc);
+        earlier.append(&mut all_lines);
+        all_lines = earlier;
+    }
+    let scroll_line = if first < top {
+        all_lines.len().saturating_sub(inner_height)
     } else {
         0
     };
 
-    // Clamp scroll so we don't go past the end
-    let max_scroll_line = all_lines.len().saturating_sub(inner_height);
-    let scroll_line = scroll_line.min(max_scroll_line);
-
     let visible_lines: Vec<Line> = all_lines
         .into_iter()
         .skip(scroll_line)
@@ -1135,3 +1560,107 @@ fn draw_message_thread(
         frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
     }
 }
+
+/// One message of the thread: header, thinking, text and tool calls, each capped
+fn message_lines(
+    app: &App,
+    msg: &crate::models::ConversationMessage,
+    focused: bool,
+    tc: &ThemeColors,
+) -> Vec<Line<'static>> {
+    let max_lines_per_msg: usize = 8;
+    let mut all_lines: Vec<Line<'static>> = Vec::new();
+
+    // Turns that only return tool results; the results show under their calls
+    if msg.role == "user" && msg.content.is_empty() {
+        all_lines.push(Line::from(Span::styled(
+            "   ↳ tool results",
+            Style::default().fg(tc.muted).add_modifier(Modifier::DIM),
+        )));
+        all_lines.push(Line::from(""));
+        return all_lines;
+    }
+
+    let (role_label, role_color) = if msg.role == "user" {
+        ("You", tc.accent)
+    } else {
+        ("Assistant", tc.token_output)
+    };
+
+    let ts = format_relative(&msg.timestamp);
+    let rule = if focused { "▶▶" } else { "──" };
+
+    // Role header
+    all_lines.push(Line::from(vec![
+        Span::styled(
+            format!("{} {} ", rule, role_label),
+            Style::default().fg(role_color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(ts, Style::default().fg(tc.muted)),
+        Span::styled(
+            " ──────────────────────────────────────────",
+            Style::default().fg(tc.border),
+        ),
+    ]));
+
+    // Thinking stays collapsed to one dimmed line unless expanded
+    if msg.thinking_blocks > 0 {
+        let dim = Style::default()
+            .fg(tc.muted)
+            .add_modifier(Modifier::DIM | Modifier::ITALIC);
+        let marker = if app.expand_thinking { "▾" } else { "▸" };
+        all_lines.push(Line::from(Span::styled(
+            format!("  {} Thinking ({} chars)", marker, format_number(msg.thinking_chars)),
+            dim,
+        )));
+        if app.expand_thinking {
+            let thinking_lines: Vec<&str> = msg.thinking.lines().collect();
+            for line in thinking_lines.iter().take(max_lines_per_msg) {
+                all_lines.push(Line::from(Span::styled(format!("    {}", line), dim)));
+            }
+            if thinking_lines.len() > max_lines_per_msg {
+                all_lines.push(Line::from(Span::styled(
+                    format!("    ... ({} more lines)", thinking_lines.len() - max_lines_per_msg),
+                    dim,
+                )));
+            }
+        }
+    }
+
+    // Content lines: assistant text is Markdown, prompts keep their tag colouring
+    let content = &msg.content;
+    if content.is_empty() && msg.tool_uses.is_empty() {
+        all_lines.push(Line::from(Span::styled(
+            "  (no text content)",
+            Style::default().fg(tc.muted),
+        )));
+    } else if !content.is_empty() {
+        let content_lines: Vec<Line<'static>> = if msg.role == "user" {
+            content
+                .lines()
+                .map(|line| Line::from(style_xml_content(&truncate(line, 200), tc.fg, tc.xml_tag)))
+                .collect()
+        } else {
+            markdown::render(content, tc)
+        };
+        let total = content_lines.len();
+        for mut line in content_lines.into_iter().take(max_lines_per_msg) {
+            line.spans.insert(0, Span::raw("  "));
+            all_lines.push(line);
+        }
+        if total > max_lines_per_msg {
+            all_lines.push(Line::from(Span::styled(
+                format!("  ... ({} more lines)", total - max_lines_per_msg),
+                Style::default().fg(tc.muted),
+            )));
+        }
+    }
+
+    for tool in &msg.tool_uses {
+        all_lines.extend(tool_call_lines(tool, app.tool_expanded(&tool.id), max_lines_per_msg, tc));
+    }
+
+    // Blank separator line
+    all_lines.push(Line::from(""));
+    all_lines
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::cursor_scanner::{cursor_global_db_path, cursor_workspace_storage_dir};
+use crate::scanner::projects_dir;
+
+/// Quiet period before a burst of writes is reported as one change
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Cursor writes SQLite in several steps, so wait longer before reporting
+const CURSOR_DEBOUNCE: Duration = Duration::from_millis(2000);
+
+/// What changed during a debounced burst of filesystem events
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Change {
+    pub claude: bool,
+    pub cursor: bool,
+}
+
+/// Watch the Claude projects dir and the Cursor databases, calling `on_change`
+/// once per debounced burst. Blocks until `on_change` returns false.
+pub fn watch_for_changes(
+    claude_roots: &[PathBuf],
+    cursor_user_dirs: &[PathBuf],
+    mut on_change: impl FnMut(Change) -> bool,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx)?;
+
+    let projects_dirs: Vec<PathBuf> = claude_roots.iter().map(|r| projects_dir(r)).collect();
+    for dir in &projects_dirs {
+        if dir.exists() {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+        }
+    }
+
+    let cursor_dirs: Vec<PathBuf> = cursor_user_dirs
+        .iter()
+        .flat_map(|u| {
+            [
+                cursor_global_db_path(u).parent().map(Path::to_path_buf),
+                Some(cursor_workspace_storage_dir(u)),
+            ]
+        })
+        .flatten()
+        .filter(|d| d.exists())
+        .collect();
+    for dir in &cursor_dirs {
+        // Not fatal: Claude changes are still reported
+        let _ = watcher.watch(dir, RecursiveMode::Recursive);
+    }
+
+    loop {
+        // Block until something happens
+        let mut change = Change::default();
+        match rx.recv() {
+            Ok(Ok(event)) => classify(&event, &projects_dirs, &cursor_dirs, &mut change),
+            Ok(Err(_)) => continue,
+            Err(_) => return Ok(()),
+        }
+
+        // Drain until the burst goes quiet
+        loop {
+            let wait = if change.cursor { CURSOR_DEBOUNCE } else { DEBOUNCE };
+            match rx.recv_timeout(wait) {
+                Ok(Ok(event)) => classify(&event, &projects_dirs, &cursor_dirs, &mut change),
+                Ok(Err(_)) => {}
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+
+        if (change.claude || change.cursor) && !on_change(change) {
+            return Ok(());
+        }
+    }
+}
+
+fn classify(event: &Event, projects_dirs: &[PathBuf], cursor_dirs: &[PathBuf], change: &mut Change) {
+    if event.kind.is_access() {
+        return;
+    }
+
+    for path in &event.paths {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if projects_dirs.iter().any(|d| path.starts_with(d)) && name.ends_with(".jsonl") {
+            change.claude = true;
+        } else if cursor_dirs.iter().any(|d| path.starts_with(d)) && name.starts_with("state.vscdb")
+        {
+            change.cursor = true;
+        }
+    }
+}
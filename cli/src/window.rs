@@ -0,0 +1,131 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use std::time::SystemTime;
+
+/// Global time-window flags shared by the TUI and every subcommand
+#[derive(clap::Args, Debug, Default)]
+pub struct WindowArgs {
+    /// Only count activity on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, global = true, conflicts_with = "last")]
+    since: Option<String>,
+
+    /// Only count activity on or before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, global = true)]
+    until: Option<String>,
+
+    /// Only count recent activity, e.g. 12h, 7d, 4w
+    #[arg(long, global = true)]
+    last: Option<String>,
+}
+
+impl WindowArgs {
+    pub fn to_window(&self) -> Result<TimeWindow> {
+        let since = match (&self.since, &self.last) {
+            (Some(s), _) => Some(parse_bound(s, false)?),
+            (None, Some(l)) => Some(
+                Utc::now()
+                    .checked_sub_signed(parse_span(l)?)
+                    .with_context(|| format!("Span '{}' reaches too far back", l))?,
+            ),
+            (None, None) => None,
+        };
+        let until = match &self.until {
+            Some(u) => Some(parse_bound(u, true)?),
+            None => None,
+        };
+
+        if let (Some(s), Some(u)) = (since, until) {
+            if s > u {
+                bail!("--since must be before --until");
+            }
+        }
+
+        Ok(TimeWindow { since, until })
+    }
+}
+
+/// Inclusive time range used to clip sessions per message
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeWindow {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeWindow {
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, t: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|s| t >= s) && self.until.is_none_or(|u| t <= u)
+    }
+
+    /// Check an event timestamp. Events without a parseable timestamp are kept.
+    pub fn contains_ts(&self, ts: &str) -> bool {
+        if self.is_unbounded() || ts.is_empty() {
+            return true;
+        }
+        match DateTime::parse_from_rfc3339(ts) {
+            Ok(d) => self.contains(d.with_timezone(&Utc)),
+            Err(_) => true,
+        }
+    }
+
+    /// A file last modified before the window starts cannot hold any activity inside it
+    pub fn may_contain_file(&self, modified: Option<SystemTime>) -> bool {
+        match (self.since, modified) {
+            (Some(since), Some(m)) => DateTime::<Utc>::from(m) >= since,
+            _ => true,
+        }
+    }
+
+    /// Short human-readable description, e.g. "2024-05-01 → now"
+    pub fn label(&self) -> String {
+        let fmt = |d: DateTime<Utc>| d.with_timezone(&Local).format("%Y-%m-%d").to_string();
+        match (self.since, self.until) {
+            (None, None) => "all time".to_string(),
+            (Some(s), None) => format!("{} → now", fmt(s)),
+            (None, Some(u)) => format!("… → {}", fmt(u)),
+            (Some(s), Some(u)) => format!("{} → {}", fmt(s), fmt(u)),
+        }
+    }
+}
+
+/// Parse a date bound. Plain dates are local days; `end_of_day` makes them inclusive.
+fn parse_bound(s: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(d) = DateTime::parse_from_rfc3339(s) {
+        return Ok(d.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD or RFC 3339", s))?;
+    let naive = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    }
+    .context("Invalid time of day")?;
+
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .with_context(|| format!("Date '{}' does not exist in the local timezone", s))
+}
+
+/// Parse a span such as `12h`, `7d` or `4w`
+fn parse_span(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let n: i64 = num
+        .parse()
+        .with_context(|| format!("Invalid span '{}', expected e.g. 12h, 7d or 4w", s))?;
+
+    let span = match unit {
+        "h" => Duration::try_hours(n),
+        "d" | "" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => bail!("Unknown unit '{}' in '{}', expected h, d or w", unit, s),
+    };
+    span.with_context(|| format!("Span '{}' is too large", s))
+}